@@ -0,0 +1,254 @@
+#![warn(missing_docs)]
+//! # Custom error derive
+//! The derive macro for error kinds used with the `custom-error` crate, use it through the
+//! `derive` feature of that crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, Lit, LitStr, Meta};
+
+/// Derive `ErrorCode` for an enum of error kinds, and `From` the enum for `CustomError` so that
+/// `.into()` creates an error with all defaults set. The defaults are only used when the error is
+/// created with `.into()`, `CustomError::coded` or `CustomError!(kind, coded)`, `CustomError::new`
+/// ignores them. The defaults are set per variant with the `error` attribute:
+/// * `code`: the stable code of the variant, defaults to the name of the variant
+/// * `title`: the default title
+/// * `help`: the default help message
+/// * `url`: the url to extra documentation
+/// * `level`: the default level, one of `"hint"`, `"help"`, `"note"`, `"info"`, `"warning"`,
+///   `"error"`, `"fatal"` or `"bug"`
+///
+/// The doc comments of the variants are used as long form description. If all variants are unit
+/// variants `ErrorKinds` is derived as well, so all explanations can be added to a `Registry` at once.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug, ErrorKind)]
+/// enum ParseError {
+///     /// The value provided is not a valid number.
+///     #[error(code = "P0001", title = "Not a number", help = "Only use the digits 0-9")]
+///     NotANumber,
+///     #[error(code = "P0002", level = "warning")]
+///     MissingHelp { line: usize },
+/// }
+///
+/// let error: CustomError<_> = ParseError::NotANumber.into();
+/// assert_eq!(error.code(), Some("P0001"));
+/// assert_eq!(CustomError!(ParseError::NotANumber, coded).code(), Some("P0001"));
+/// assert_eq!(CustomError::new(ParseError::NotANumber).code(), None);
+/// assert_eq!(ParseError::NotANumber.description(), Some("The value provided is not a valid number."));
+/// assert!(CustomError::coded(ParseError::MissingHelp { line: 1 }).is_warning());
+///
+/// #[derive(Debug, ErrorKind)]
+/// enum LintError {
+///     /// A line that is longer than 100 characters.
+///     ///
+///     /// Break the line up into multiple lines.
+///     #[error(code = "L0001", title = "Line too long")]
+///     LineTooLong,
+/// }
+///
+/// let registry = Registry::new().all_kinds::<LintError>();
+/// assert_eq!(registry.codes().collect::<Vec<_>>(), vec![("L0001", Some("Line too long"))]);
+/// ```
+#[proc_macro_derive(ErrorKind, attributes(error))]
+pub fn derive_error_kind(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive(input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// The defaults for a single variant
+struct Variant {
+    ident: syn::Ident,
+    code: String,
+    title: Option<String>,
+    help: Option<String>,
+    url: Option<String>,
+    level: Option<TokenStream2>,
+    description: Option<String>,
+}
+
+impl Variant {
+    fn parse(variant: &syn::Variant) -> syn::Result<Self> {
+        let mut output = Variant {
+            ident: variant.ident.clone(),
+            code: variant.ident.to_string(),
+            title: None,
+            help: None,
+            url: None,
+            level: None,
+            description: description(&variant.attrs),
+        };
+        for attr in variant.attrs.iter().filter(|a| a.path().is_ident("error")) {
+            attr.parse_nested_meta(|meta| {
+                let value: LitStr = meta.value()?.parse()?;
+                if meta.path.is_ident("code") {
+                    output.code = value.value();
+                } else if meta.path.is_ident("title") {
+                    output.title = Some(value.value());
+                } else if meta.path.is_ident("help") {
+                    output.help = Some(value.value());
+                } else if meta.path.is_ident("url") {
+                    output.url = Some(value.value());
+                } else if meta.path.is_ident("level") {
+                    output.level = Some(match value.value().as_str() {
+                        "hint" => quote!(::custom_error::Severity::Hint),
+                        "help" => quote!(::custom_error::Severity::Help),
+                        "note" => quote!(::custom_error::Severity::Note),
+                        "info" => quote!(::custom_error::Severity::Info),
+                        "warning" => quote!(::custom_error::Severity::Warning),
+                        "error" => quote!(::custom_error::Severity::Error),
+                        "fatal" => quote!(::custom_error::Severity::Fatal),
+                        "bug" => quote!(::custom_error::Severity::Bug),
+                        _ => {
+                            return Err(syn::Error::new(
+                                value.span(),
+                                "expected one of \"hint\", \"help\", \"note\", \"info\", \"warning\", \"error\", \"fatal\" or \"bug\"",
+                            ))
+                        }
+                    });
+                } else {
+                    return Err(
+                        meta.error("expected one of `code`, `title`, `help`, `url` or `level`")
+                    );
+                }
+                Ok(())
+            })?;
+        }
+        Ok(output)
+    }
+}
+
+/// Join all doc comments into a single description
+fn description(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .filter_map(|a| match &a.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(doc) => Some(doc.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(' ')
+                .unwrap_or(&line)
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+    let description = lines.join("\n").trim().to_string();
+    (!description.is_empty()).then_some(description)
+}
+
+/// Wrap an optional value in `Some` or give `None`
+fn option<T: quote::ToTokens>(value: &Option<T>) -> TokenStream2 {
+    match value {
+        Some(value) => quote!(::core::option::Option::Some(#value)),
+        None => quote!(::core::option::Option::None),
+    }
+}
+
+fn derive(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "ErrorKind can only be derived for enums",
+            ))
+        }
+    };
+    let variants = data
+        .variants
+        .iter()
+        .map(Variant::parse)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    // Generate a match over all variants, with the value given for every variant
+    let method = |value: &dyn Fn(&Variant) -> TokenStream2| {
+        let arms = variants.iter().map(|variant| {
+            let ident = &variant.ident;
+            let value = value(variant);
+            quote!(Self::#ident { .. } => #value,)
+        });
+        quote!(match *self { #(#arms)* })
+    };
+    let code = method(&|v| {
+        let code = &v.code;
+        quote!(#code)
+    });
+    let title = method(&|v| option(&v.title));
+    let description = method(&|v| option(&v.description));
+    let help = method(&|v| option(&v.help));
+    let url = method(&|v| {
+        let url = v
+            .url
+            .as_ref()
+            .map(|url| quote!(::std::string::ToString::to_string(#url)));
+        option(&url)
+    });
+    let level = method(&|v| option(&v.level));
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // The list of all kinds can only be made if all variants are unit variants
+    let kinds = data
+        .variants
+        .iter()
+        .all(|v| matches!(v.fields, Fields::Unit))
+        .then(|| {
+            let idents = variants.iter().map(|v| &v.ident);
+            quote! {
+                impl #impl_generics ::custom_error::ErrorKinds for #name #ty_generics #where_clause {
+                    fn kinds() -> ::std::vec::Vec<Self> {
+                        ::std::vec![#(Self::#idents),*]
+                    }
+                }
+            }
+        });
+    Ok(quote! {
+        impl #impl_generics ::custom_error::ErrorCode for #name #ty_generics #where_clause {
+            fn code(&self) -> &'static str {
+                #code
+            }
+
+            fn title(&self) -> ::core::option::Option<&'static str> {
+                #title
+            }
+
+            fn description(&self) -> ::core::option::Option<&'static str> {
+                #description
+            }
+
+            fn help(&self) -> ::core::option::Option<&'static str> {
+                #help
+            }
+
+            fn url(&self) -> ::core::option::Option<::std::string::String> {
+                #url
+            }
+
+            fn level(&self) -> ::core::option::Option<::custom_error::Severity> {
+                #level
+            }
+        }
+
+        impl #impl_generics ::core::convert::From<#name #ty_generics>
+            for ::custom_error::CustomError<#name #ty_generics> #where_clause
+        {
+            fn from(kind: #name #ty_generics) -> Self {
+                ::custom_error::CustomError::coded(kind)
+            }
+        }
+
+        #kinds
+    })
+}
@@ -1,10 +1,6 @@
-use custom_error::{Context, CustomError, CustomErrors};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use custom_error::{Context, CustomError, CustomErrors, Source};
 
 pub mod simple {
-    use custom_error::Highlight;
-
     use super::*;
 
     #[derive(Debug)]
@@ -13,70 +9,58 @@ pub mod simple {
         NotANumber,
         MissingHelp,
         IncorrectNumberOfArguments,
-        InvalidLine,
+        InvalidFile,
     }
 
     pub fn parse(path: &str) -> Result<Vec<usize>, CustomErrors<ParseError>> {
+        let source = match Source::read(path) {
+            Ok(source) => source,
+            Err(e) => {
+                return Err(std::iter::once(
+                    CustomError!(ParseError::InvalidFile).message(e.to_string()),
+                )
+                .collect())
+            }
+        };
         let mut output = Vec::new();
         let mut errors = CustomErrors::new();
-        for (linenumber, line) in BufReader::new(File::open(path).unwrap())
-            .lines()
-            .enumerate()
-        {
-            if let Ok(line) = line {
-                if line.starts_with('#') {
-                    continue;
-                }
-                let pieces: Vec<_> = split_with_offset(&line);
-                if pieces.len() != 2 {
-                    errors += CustomError::new(ParseError::IncorrectNumberOfArguments)
-                        .context(Context::line(&line).linenumber(linenumber).file(path));
-                    continue;
-                }
-                if pieces[0].0 != "help" {
-                    errors += CustomError::new(ParseError::MissingHelp)
-                        .message("A line should always start with 'help'")
-                        .context(
-                            Context::line(&line)
-                                .linenumber(linenumber)
-                                .file(path)
-                                .highlight((0, pieces[0].1, pieces[0].2)),
-                        );
-                }
-                match pieces[1].0.parse::<usize>() {
-                    Ok(n) => output.push(n),
-                    Err(e) => {
-                        errors += CustomError::new(ParseError::NotANumber)
-                            .message("After the 'help' a number should written")
-                            .help(e.to_string())
-                            .context(
-                                Context::line(&line)
-                                    .linenumber(linenumber)
-                                    .file(path)
-                                    .highlight({
-                                        let high = Highlight::new(0, pieces[1].1, pieces[1].2);
-                                        if line[pieces[1].1..pieces[1].1 + pieces[1].2]
-                                            .contains(['l', 'I', 'O'])
-                                        {
-                                            high.note(
-                                            "It contains characters which look a lot like digits",
-                                        )
-                                        } else {
-                                            high
-                                        }
-                                    }),
-                            )
-                    }
+        for (offset, line) in source.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let pieces: Vec<_> = split_with_offset(line);
+            if pieces.len() != 2 {
+                errors += CustomError::new(ParseError::IncorrectNumberOfArguments)
+                    .context(Context::from_span(&source, offset..offset + line.len()));
+                continue;
+            }
+            if pieces[0].0 != "help" {
+                errors += CustomError::new(ParseError::MissingHelp)
+                    .message("A line should always start with 'help'")
+                    .context(Context::from_span(
+                        &source,
+                        offset + pieces[0].1..offset + pieces[0].1 + pieces[0].2,
+                    ));
+            }
+            match pieces[1].0.parse::<usize>() {
+                Ok(n) => output.push(n),
+                Err(e) => {
+                    let context = Context::from_span(
+                        &source,
+                        offset + pieces[1].1..offset + pieces[1].1 + pieces[1].2,
+                    );
+                    errors += CustomError::new(ParseError::NotANumber)
+                        .message("After the 'help' a number should written")
+                        .help(e.to_string())
+                        .context(if pieces[1].0.contains(['l', 'I', 'O']) {
+                            context.note("It contains characters which look a lot like digits")
+                        } else {
+                            context
+                        })
                 }
-            } else {
-                errors += CustomError!(ParseError::InvalidLine);
             }
         }
-        if errors.is_empty() {
-            Ok(output)
-        } else {
-            Err(errors)
-        }
+        errors.into_result(output)
     }
 }
 
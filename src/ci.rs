@@ -0,0 +1,242 @@
+use crate::error::Severity;
+use crate::render::{ErrorView, Renderer, Summary};
+use std::fmt::Write;
+
+/// The position of an error in a file, the file and 1 based line and column ranges
+struct Position<'a> {
+    file: &'a str,
+    line: usize,
+    column: usize,
+    end_line: usize,
+    end_column: usize,
+}
+
+impl<'a> Position<'a> {
+    /// The position of the first highlight (or first line if there are no highlights) of the
+    /// first context with a file. The linenumbers are seen as 1 based (as set by
+    /// [Context::from_span](crate::Context::from_span)) and columns are counted in chars.
+    fn new(error: &ErrorView<'a>) -> Option<Self> {
+        let context = error.contexts.iter().find(|c| c.file.is_some())?;
+        let first_line = context.linenumber.unwrap_or(1);
+        Some(match context.highlights.first() {
+            Some(highlight) => {
+                let (end_line, end_column) = highlight.end_position();
+                Position {
+                    file: context.file?,
+                    line: first_line + highlight.line,
+                    column: context.char_column(highlight.line, highlight.column) + 1,
+                    end_line: first_line + end_line,
+                    end_column: context.char_column(end_line, end_column) + 1,
+                }
+            }
+            None => Position {
+                file: context.file?,
+                line: first_line,
+                column: 1,
+                end_line: first_line,
+                end_column: context.lines.first().map_or(0, |l| l.chars().count()) + 1,
+            },
+        })
+    }
+}
+
+/// A renderer which outputs GitHub Actions workflow commands, so that the errors are shown as
+/// annotations on the files in pull requests. Errors become `error`, warnings `warning` and info
+/// messages `notice` annotations.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// let source = Source::new("numbers.txt", "help 123\nhelp l23\n");
+/// let error = CustomError::new(ErrorType::NotANumber)
+///     .message("Not a number")
+///     .context(Context::from_span(&source, 14..17));
+/// let mut output = String::new();
+/// error.render(&GithubRenderer, &mut output).unwrap();
+/// assert_eq!(output, "::error file=numbers.txt,line=2,endLine=2,col=6,endColumn=9::Not a number\n");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct GithubRenderer;
+
+impl GithubRenderer {
+    /// Escape the data of a workflow command
+    fn escape_data(text: &str) -> String {
+        text.replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A")
+    }
+
+    /// Escape the value of a property of a workflow command
+    fn escape_property(text: &str) -> String {
+        Self::escape_data(text)
+            .replace(':', "%3A")
+            .replace(',', "%2C")
+    }
+}
+
+impl Renderer for GithubRenderer {
+    fn render_error(&self, error: &ErrorView<'_>, out: &mut dyn Write) -> std::fmt::Result {
+        let command = match error.level {
+            Severity::Error | Severity::Fatal | Severity::Bug => "error",
+            Severity::Warning => "warning",
+            Severity::Info | Severity::Note | Severity::Help | Severity::Hint => "notice",
+        };
+        let mut properties = Vec::new();
+        if let Some(position) = Position::new(error) {
+            properties.push(format!("file={}", Self::escape_property(position.file)));
+            properties.push(format!("line={}", position.line));
+            properties.push(format!("endLine={}", position.end_line));
+            properties.push(format!("col={}", position.column));
+            properties.push(format!("endColumn={}", position.end_column));
+        }
+        if let Some(title) = error.title {
+            properties.push(format!("title={}", Self::escape_property(title)));
+        }
+        writeln!(
+            out,
+            "::{}{}{}::{}",
+            command,
+            if properties.is_empty() { "" } else { " " },
+            properties.join(","),
+            Self::escape_data(&error.plain_message())
+        )
+    }
+
+    fn render_errors(
+        &self,
+        errors: &[ErrorView<'_>],
+        _summary: &Summary,
+        out: &mut dyn Write,
+    ) -> std::fmt::Result {
+        for error in errors {
+            self.render_error(error, out)?;
+        }
+        Ok(())
+    }
+
+    fn render_summary(&self, _summary: &Summary, _out: &mut dyn Write) -> std::fmt::Result {
+        Ok(())
+    }
+}
+
+/// A renderer which outputs a GitLab Code Quality report, a JSON array with an issue for every
+/// error. Errors get the severity `major`, warnings `minor` and info messages `info`. The
+/// fingerprint is a 64 bit FNV-1a hash of the kind, message and position of the error, so it stays
+/// the same between runs and Rust versions. Code Quality issues need a file, so errors without a
+/// context with a file are left out of the report.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// impl ErrorCode for ErrorType {
+///     fn code(&self) -> &'static str {
+///         "P0001"
+///     }
+/// }
+/// let source = Source::new("numbers.txt", "help 123\nhelp l23\n");
+/// let mut errors = CustomErrors::new();
+/// errors += CustomError::coded(ErrorType::NotANumber)
+///     .message("Not a number")
+///     .context(Context::from_span(&source, 14..17));
+/// // Errors without a file are left out
+/// errors += CustomError::coded(ErrorType::NotANumber).context(Context::line("help l23"));
+/// let mut output = String::new();
+/// errors.render(&GitlabRenderer, &mut output).unwrap();
+/// assert!(output.contains(r#""fingerprint":"332b35d13a2170a1""#));
+/// assert!(output.contains(r#""location":{"path":"numbers.txt","lines":{"begin":2,"end":2}}"#));
+/// assert_eq!(output.matches("fingerprint").count(), 1);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct GitlabRenderer;
+
+impl GitlabRenderer {
+    /// Write the given text as a JSON string
+    fn string(text: &str) -> String {
+        let mut output = String::with_capacity(text.len() + 2);
+        output.push('"');
+        for c in text.chars() {
+            match c {
+                '"' => output.push_str("\\\""),
+                '\\' => output.push_str("\\\\"),
+                '\n' => output.push_str("\\n"),
+                '\r' => output.push_str("\\r"),
+                '\t' => output.push_str("\\t"),
+                c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+                c => output.push(c),
+            }
+        }
+        output.push('"');
+        output
+    }
+
+    /// The 64 bit FNV-1a hash of the given parts, each part is followed by a zero byte to keep
+    /// the parts apart
+    fn fingerprint(parts: &[&str]) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in parts.iter().flat_map(|part| part.bytes().chain([0])) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        hash
+    }
+}
+
+impl Renderer for GitlabRenderer {
+    fn render_error(&self, error: &ErrorView<'_>, out: &mut dyn Write) -> std::fmt::Result {
+        let Some(position) = Position::new(error) else {
+            return Ok(());
+        };
+        let message = error.plain_message();
+        let (path, begin, end) = (position.file, position.line, position.end_line);
+        let fingerprint = Self::fingerprint(&[
+            error.identifier(),
+            &message,
+            path,
+            &begin.to_string(),
+            &end.to_string(),
+        ]);
+
+        write!(
+            out,
+            r#"{{"description":{},"check_name":{},"fingerprint":"{:016x}","severity":"{}","location":{{"path":{},"lines":{{"begin":{},"end":{}}}}}}}"#,
+            Self::string(&message),
+            Self::string(error.identifier()),
+            fingerprint,
+            match error.level {
+                Severity::Bug => "blocker",
+                Severity::Fatal => "critical",
+                Severity::Error => "major",
+                Severity::Warning => "minor",
+                Severity::Info | Severity::Note | Severity::Help | Severity::Hint => "info",
+            },
+            Self::string(path),
+            begin,
+            end,
+        )
+    }
+
+    fn render_errors(
+        &self,
+        errors: &[ErrorView<'_>],
+        _summary: &Summary,
+        out: &mut dyn Write,
+    ) -> std::fmt::Result {
+        write!(out, "[")?;
+        let errors = errors.iter().filter(|error| Position::new(error).is_some());
+        for (index, error) in errors.enumerate() {
+            if index > 0 {
+                write!(out, ",")?;
+            }
+            self.render_error(error, out)?;
+        }
+        writeln!(out, "]")
+    }
+
+    fn render_summary(&self, _summary: &Summary, _out: &mut dyn Write) -> std::fmt::Result {
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use crate::error::Severity;
+
+/// A stable identifier for every kind of error, like the error codes of rustc (`E0308`). Unlike
+/// the name of the type and variant, which is shown otherwise, the code stays the same when
+/// types are moved or renamed. Use [CustomError::coded](crate::CustomError::coded) or
+/// `CustomError!(kind, coded)` to create an error with the code and the defaults given by this
+/// trait. [CustomError::new](crate::CustomError::new) and `CustomError!(kind)` ignore this trait,
+/// errors created with those show the name of the type and variant and have no defaults set.
+/// With the `derive` feature this trait can be derived with `#[derive(ErrorKind)]`.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ParseError {
+///     NotANumber,
+///     MissingHelp,
+/// }
+///
+/// impl ErrorCode for ParseError {
+///     fn code(&self) -> &'static str {
+///         match self {
+///             ParseError::NotANumber => "P0001",
+///             ParseError::MissingHelp => "P0002",
+///         }
+///     }
+///
+///     fn title(&self) -> Option<&'static str> {
+///         match self {
+///             ParseError::NotANumber => Some("Not a number"),
+///             ParseError::MissingHelp => Some("Missing 'help'"),
+///         }
+///     }
+/// }
+///
+/// let error = CustomError::coded(ParseError::NotANumber);
+/// assert_eq!(error.code(), Some("P0001"));
+/// assert!(error.with_colour(ColourChoice::Never).to_string().starts_with("error[P0001]: Not a number"));
+/// ```
+pub trait ErrorCode {
+    /// The code for this kind of error, for example `"P0001"`
+    fn code(&self) -> &'static str;
+
+    /// The default title for this kind of error
+    fn title(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// A long form description of this kind of error
+    fn description(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The default help message for this kind of error
+    fn help(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// The url to extra documentation for this kind of error
+    fn url(&self) -> Option<String> {
+        None
+    }
+
+    /// The default level for this kind of error, if not given it is an error
+    fn level(&self) -> Option<Severity> {
+        None
+    }
+}
+
+/// A type with a fixed list of kinds of errors, for example an enum with only unit variants. This
+/// is used to register the explanations of all kinds at once, see [Registry::all_kinds](crate::Registry::all_kinds).
+/// It is derived by `#[derive(ErrorKind)]` if all variants are unit variants.
+pub trait ErrorKinds: ErrorCode + Sized {
+    /// All kinds of errors of this type
+    fn kinds() -> Vec<Self>;
+}
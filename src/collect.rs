@@ -0,0 +1,124 @@
+use crate::error::CustomError;
+use crate::errors::CustomErrors;
+use std::cell::RefCell;
+
+/// A trait to collect an iterator of results into a list of all values, or all errors if there
+/// were any. In contrast to collecting into a `Result<Vec<_>, _>` the iteration does not stop at
+/// the first error.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// let parse = |input: &str| {
+///     input
+///         .parse::<usize>()
+///         .map_err(|_| CustomError::new(ErrorType::NotANumber).context(Context::line(input)))
+/// };
+/// assert_eq!(["1", "2"].into_iter().map(parse).collect_errors().unwrap(), vec![1, 2]);
+/// let errors = ["1", "l2", "3", "4O"].into_iter().map(parse).collect_errors().unwrap_err();
+/// assert_eq!(errors.iter().count(), 2);
+/// ```
+pub trait CollectErrors<T, E> {
+    /// Collect all values, or all errors if any of the results is an error (of any level).
+    fn collect_errors(self) -> Result<Vec<T>, CustomErrors<E>>;
+}
+
+impl<T, E, I: Iterator<Item = Result<T, CustomError<E>>>> CollectErrors<T, E> for I {
+    fn collect_errors(self) -> Result<Vec<T>, CustomErrors<E>> {
+        let mut values = Vec::new();
+        let mut errors = CustomErrors::new();
+        for item in self {
+            match item {
+                Ok(value) => values.push(value),
+                Err(error) => errors += error,
+            }
+        }
+        if errors.is_empty() {
+            Ok(values)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// A collection of errors which can be shared between functions, so that functions deep in a
+/// parser can report errors and continue without returning them. Errors are added with a shared
+/// reference, so the sink can be passed to all functions that need it.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+///     UnusedValue,
+/// }
+///
+/// fn parse_number(sink: &ErrorSink<ErrorType>, input: &str) -> Option<usize> {
+///     sink.report(input.parse().map_err(|_| {
+///         CustomError::new(ErrorType::NotANumber).context(Context::line(input))
+///     }))
+/// }
+///
+/// fn parse(input: &str) -> Result<Vec<usize>, CustomErrors<ErrorType>> {
+///     let sink = ErrorSink::new();
+///     let numbers = input.split(',').filter_map(|n| parse_number(&sink, n)).collect();
+///     sink.push(CustomError::new(ErrorType::UnusedValue).warning());
+///     sink.into_result(numbers)
+/// }
+///
+/// assert_eq!(parse("1,2,3").unwrap(), vec![1, 2, 3]);
+/// assert_eq!(parse("1,l2,3").unwrap_err().iter().count(), 2);
+/// ```
+#[derive(Debug)]
+pub struct ErrorSink<T> {
+    errors: RefCell<CustomErrors<T>>,
+}
+
+impl<T> ErrorSink<T> {
+    /// Create an empty sink.
+    pub fn new() -> Self {
+        Self::from(CustomErrors::new())
+    }
+
+    /// Add an error to the sink, this returns true if a limit of the collection is reached, see
+    /// [CustomErrors::push].
+    pub fn push(&self, error: CustomError<T>) -> bool {
+        self.errors.borrow_mut().push(error)
+    }
+
+    /// Add the error of the given result to the sink, and return the value if there was no error.
+    pub fn report<V>(&self, result: Result<V, CustomError<T>>) -> Option<V> {
+        result.map_err(|error| self.push(error)).ok()
+    }
+
+    /// Check if there are any errors (not warnings or other messages) in this sink.
+    pub fn any_errors(&self) -> bool {
+        self.errors.borrow().any_errors()
+    }
+
+    /// Get all errors added to this sink.
+    pub fn into_errors(self) -> CustomErrors<T> {
+        self.errors.into_inner()
+    }
+
+    /// Return the given value if there are no errors in this sink, see [CustomErrors::into_result].
+    pub fn into_result<V>(self, value: V) -> Result<V, CustomErrors<T>> {
+        self.into_errors().into_result(value)
+    }
+}
+
+impl<T> Default for ErrorSink<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Use the given collection in the sink, to keep its errors, limits and policy.
+impl<T> From<CustomErrors<T>> for ErrorSink<T> {
+    fn from(errors: CustomErrors<T>) -> Self {
+        ErrorSink {
+            errors: RefCell::new(errors),
+        }
+    }
+}
@@ -1,42 +1,216 @@
 #[cfg(feature = "ansi_term")]
-use ansi_term::Colour::{Blue, Fixed, Green, Red, Yellow};
+use ansi_term::Colour::{Blue, Cyan, Fixed, Green, Red, Yellow};
+use std::cell::Cell;
+use std::fmt::{Display, Formatter};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
 
-pub fn red(input: impl Into<String>) -> String {
-    if cfg!(feature = "ansi_term") {
-        Red.paint(input.into()).to_string()
-    } else {
-        input.into()
+/// When to use colours in the output. Colours are only ever used if the `ansi_term` feature is enabled.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// // Never use colours, for example when writing to a log file
+/// ColourChoice::Never.set_global();
+/// // Or decide for a single render
+/// let error = CustomError::new(ErrorType::NotANumber);
+/// println!("{}", error.with_colour(ColourChoice::Always));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub enum ColourChoice {
+    /// Always use colours
+    Always,
+    /// Never use colours
+    Never,
+    /// Use colours if the `NO_COLOR` environment variable is not set and the output is written to
+    /// a terminal, or if `CLICOLOR_FORCE` is set (and not `0`). When the destination is not known,
+    /// like for [Display], both stdout and stderr have to be terminals.
+    #[default]
+    Auto,
+}
+
+/// The globally set colour choice, stored as the index of the variant
+static GLOBAL_CHOICE: AtomicU8 = AtomicU8::new(2);
+
+thread_local! {
+    /// If colours are used in the render that is currently happening on this thread
+    static ACTIVE: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+impl ColourChoice {
+    /// Set this as the colour choice for all output that does not specify its own choice.
+    pub fn set_global(self) {
+        GLOBAL_CHOICE.store(
+            match self {
+                ColourChoice::Always => 0,
+                ColourChoice::Never => 1,
+                ColourChoice::Auto => 2,
+            },
+            Ordering::Relaxed,
+        );
+    }
+
+    /// Get the globally set colour choice, this is [ColourChoice::Auto] if never set.
+    pub fn global() -> Self {
+        match GLOBAL_CHOICE.load(Ordering::Relaxed) {
+            0 => ColourChoice::Always,
+            1 => ColourChoice::Never,
+            _ => ColourChoice::Auto,
+        }
+    }
+
+    /// Determine if colours should be used based on this choice and the current environment,
+    /// when the destination of the output is not known.
+    pub fn use_colour(self) -> bool {
+        self.decide(|| std::io::stdout().is_terminal() && std::io::stderr().is_terminal())
+    }
+
+    /// Determine if colours should be used based on this choice and the current environment, for
+    /// output written to the given stream.
+    /// ```
+    /// use custom_error::*;
+    /// assert!(!ColourChoice::Never.use_colour_for(&std::io::stderr()));
+    /// ```
+    pub fn use_colour_for(self, stream: &impl IsTerminal) -> bool {
+        self.decide(|| stream.is_terminal())
+    }
+
+    /// Determine if colours should be used, with the given check if the output is a terminal
+    fn decide(self, terminal: impl FnOnce() -> bool) -> bool {
+        match self {
+            ColourChoice::Always => true,
+            ColourChoice::Never => false,
+            ColourChoice::Auto => {
+                let set = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+                if set("NO_COLOR").is_some() {
+                    false
+                } else if set("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+                    true
+                } else {
+                    terminal()
+                }
+            }
+        }
     }
 }
 
-pub fn yellow(input: impl Into<String>) -> String {
-    if cfg!(feature = "ansi_term") {
-        Yellow.paint(input.into()).to_string()
-    } else {
-        input.into()
+/// Display an item with the given colour choice, see [CustomError::with_colour](crate::CustomError::with_colour).
+#[derive(Debug, Clone, Copy)]
+pub struct WithColour<'a, D: ?Sized> {
+    item: &'a D,
+    choice: ColourChoice,
+}
+
+impl<'a, D: ?Sized> WithColour<'a, D> {
+    pub(crate) fn new(item: &'a D, choice: ColourChoice) -> Self {
+        Self { item, choice }
     }
 }
 
-pub fn green(input: impl Into<String>) -> String {
-    if cfg!(feature = "ansi_term") {
-        Green.paint(input.into()).to_string()
-    } else {
-        input.into()
+impl<D: Display + ?Sized> Display for WithColour<'_, D> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        scoped(Some(self.choice), || self.item.fmt(f))
     }
 }
 
-pub fn blue(input: impl Into<String>) -> String {
-    if cfg!(feature = "ansi_term") {
-        Blue.paint(input.into()).to_string()
-    } else {
-        input.into()
+/// Run the given render with the given colour choice. If no choice is given the choice of the
+/// surrounding render is used, or the global choice if this is the outermost render. This makes
+/// sure the environment is only inspected once per render.
+pub(crate) fn scoped<R>(choice: Option<ColourChoice>, render: impl FnOnce() -> R) -> R {
+    scoped_with(choice, ColourChoice::use_colour, render)
+}
+
+/// Run the given render with the given colour choice like [scoped], for output written to the
+/// given stream.
+pub(crate) fn scoped_for<R>(
+    choice: Option<ColourChoice>,
+    stream: &impl IsTerminal,
+    render: impl FnOnce() -> R,
+) -> R {
+    scoped_with(choice, |choice| choice.use_colour_for(stream), render)
+}
+
+/// Run the given render with the given colour choice, decided with the given function
+fn scoped_with<R>(
+    choice: Option<ColourChoice>,
+    use_colour: impl FnOnce(ColourChoice) -> bool,
+    render: impl FnOnce() -> R,
+) -> R {
+    /// Restore the previous state, even if the render panics
+    struct Reset(Option<bool>);
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            ACTIVE.with(|active| active.set(self.0));
+        }
     }
+
+    let previous = ACTIVE.with(|active| active.get());
+    let current = match (choice, previous) {
+        (Some(choice), _) => use_colour(choice),
+        (None, Some(previous)) => previous,
+        (None, None) => use_colour(ColourChoice::global()),
+    };
+    ACTIVE.with(|active| active.set(Some(current)));
+    let _reset = Reset(previous);
+    render()
 }
 
-pub fn grey(input: impl Into<String>) -> String {
-    if cfg!(feature = "ansi_term") {
-        Fixed(8).paint(input.into()).to_string()
-    } else {
-        input.into()
+/// The colours used in the output
+#[derive(Clone, Copy)]
+enum Paint {
+    Red,
+    Yellow,
+    Green,
+    Blue,
+    Cyan,
+    Grey,
+}
+
+fn paint(colour: Paint, input: impl Into<String>) -> String {
+    let input = input.into();
+    #[cfg(feature = "ansi_term")]
+    if ACTIVE
+        .with(|active| active.get())
+        .unwrap_or_else(|| ColourChoice::global().use_colour())
+    {
+        return match colour {
+            Paint::Red => Red,
+            Paint::Yellow => Yellow,
+            Paint::Green => Green,
+            Paint::Blue => Blue,
+            Paint::Cyan => Cyan,
+            Paint::Grey => Fixed(8),
+        }
+        .paint(input)
+        .to_string();
     }
+    #[cfg(not(feature = "ansi_term"))]
+    let _ = colour;
+    input
+}
+
+pub fn red(input: impl Into<String>) -> String {
+    paint(Paint::Red, input)
+}
+
+pub fn yellow(input: impl Into<String>) -> String {
+    paint(Paint::Yellow, input)
+}
+
+pub fn green(input: impl Into<String>) -> String {
+    paint(Paint::Green, input)
+}
+
+pub fn blue(input: impl Into<String>) -> String {
+    paint(Paint::Blue, input)
+}
+
+pub fn cyan(input: impl Into<String>) -> String {
+    paint(Paint::Cyan, input)
+}
+
+pub fn grey(input: impl Into<String>) -> String {
+    paint(Paint::Grey, input)
 }
@@ -1,6 +1,9 @@
 use crate::colour::*;
-use crate::error::ErrorLevel;
+use crate::error::Severity;
+use crate::render::*;
+use crate::source::Source;
 use std::fmt::{Display, Formatter, Result};
+use std::ops::Range;
 
 /// The context for an error message. This can be created using builder style methods.
 /// ```
@@ -24,11 +27,31 @@ use std::fmt::{Display, Formatter, Result};
 /// }
 /// ```
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Context {
     lines: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(rename = "line_start"))]
     linenumber: Option<usize>,
     highlights: Vec<Highlight>,
     file: Option<String>,
+    unit: OffsetUnit,
+    tab_width: usize,
+}
+
+/// The unit in which the columns and lengths of highlights are counted. Whatever the unit, the
+/// highlights are rendered based on the display width of the text, so they line up with wide
+/// characters, combining marks and tabs.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum OffsetUnit {
+    /// Offsets are given in bytes (of the UTF-8 text), like the indices used for slicing strings
+    Byte,
+    /// Offsets are given in unicode scalar values, like the results of `str::chars`
+    #[default]
+    Char,
+    /// Offsets are given in extended grapheme clusters, what users generally see as a single character
+    Grapheme,
 }
 
 impl Context {
@@ -39,6 +62,8 @@ impl Context {
             linenumber: None,
             highlights: Vec::new(),
             file: None,
+            unit: OffsetUnit::Char,
+            tab_width: 4,
         }
     }
 
@@ -49,9 +74,54 @@ impl Context {
             linenumber: None,
             highlights: Vec::new(),
             file: None,
+            unit: OffsetUnit::Char,
+            tab_width: 4,
         }
     }
 
+    /// Create a new Context from a span of byte offsets in the given source. The context contains
+    /// all lines touched by the span, it gets the (1 based) linenumber of the first line and the
+    /// file name of the source. The span itself is highlighted, if it covers multiple lines this
+    /// is a multi line highlight (see [Highlight::multiline]).
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let source = Source::new("numbers.txt", "help 123\nhelp l23\n");
+    /// let error = CustomError::new(ErrorType::NotANumber)
+    ///     .context(Context::from_span(&source, 14..17));
+    /// ```
+    pub fn from_span(source: &Source, span: Range<usize>) -> Self {
+        let start = source.floor_char_boundary(span.start);
+        let end = source.floor_char_boundary(span.end).max(start);
+        let first = source.line_index(start);
+        // The last byte in the span determines the last line, an empty span stays on the first line
+        let last = source.line_index(if end > start { end - 1 } else { end });
+        let text = source.text();
+
+        let mut context = Context::lines((first..=last).map(|index| source.line(index).unwrap()))
+            .linenumber(first + 1);
+        if let Some(name) = source.name() {
+            context = context.file(name);
+        }
+        let column = |offset: usize| {
+            let line = source.line_range(source.line_index(offset)).unwrap();
+            text[line.start..offset.clamp(line.start, line.end)]
+                .chars()
+                .count()
+        };
+        context.highlights.push(Highlight::multiline(
+            0,
+            column(start),
+            last - first,
+            // Do not count the line ending if the span ends just after it
+            column(end.min(source.line_range(last).unwrap().end)),
+        ));
+        context
+    }
+
     /// Add a linenumber for the first line in the given lines
     pub fn linenumber(self, linenumber: usize) -> Self {
         Context {
@@ -76,6 +146,43 @@ impl Context {
         self
     }
 
+    /// Set the unit in which the columns and lengths of all highlights in this context are given,
+    /// the default is [OffsetUnit::Char].
+    /// ```
+    /// use custom_error::*;
+    /// let line = "naïve = 12";
+    /// let context = Context::line(line)
+    ///     .file("config.txt")
+    ///     .linenumber(1)
+    ///     .unit(OffsetUnit::Byte)
+    ///     .highlight((line.find('=').unwrap(), 1));
+    /// // The location in the header is always given in chars
+    /// let error = CustomError::new("Invalid").context(context);
+    /// assert!(error.to_string().contains("[config.txt:1:6]"));
+    /// ```
+    pub fn unit(self, unit: OffsetUnit) -> Self {
+        Context { unit, ..self }
+    }
+
+    /// Set the number of columns a tab is expanded to when displaying the lines, the default is 4.
+    pub fn tab_width(self, tab_width: usize) -> Self {
+        Context { tab_width, ..self }
+    }
+
+    /// Add a note to the last highlight in this context, useful to annotate the highlight created by
+    /// [Context::from_span]. Does nothing if there are no highlights.
+    pub fn note(mut self, note: impl Into<String>) -> Self {
+        if let Some(highlight) = self.highlights.pop() {
+            self.highlights.push(highlight.note(note));
+        }
+        self
+    }
+
+    /// Display this context with the given colour choice, instead of the global choice.
+    pub fn with_colour(&self, choice: ColourChoice) -> WithColour<'_, Self> {
+        WithColour::new(self, choice)
+    }
+
     /// Add the name of the file where this context is located. It automatically adds linenumber information
     /// from the linenumber (if given) and column information from the highlight (if given). Which results in
     /// a location like this: `-->src/context.rs:81:53`.
@@ -85,10 +192,24 @@ impl Context {
             ..self
         }
     }
+
+    /// Set the file of this context, if it does not have a file yet
+    pub(crate) fn default_file(self, file: &str) -> Self {
+        if self.file.is_some() {
+            self
+        } else {
+            self.file(file)
+        }
+    }
 }
 
 /// A highlight in a context for an error.
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "HighlightJson", into = "HighlightJson")
+)]
 pub struct Highlight {
     /// The line offset in the list of lines for a context
     line: usize,
@@ -96,9 +217,12 @@ pub struct Highlight {
     column: usize,
     /// The length of the highlight
     length: usize,
+    /// The line offset and column where the highlight ends (exclusive), only used for highlights
+    /// spanning multiple lines
+    end: Option<(usize, usize)>,
     /// An optional note to display after the highlight
     note: Option<String>,
-    level: ErrorLevel,
+    level: Severity,
 }
 
 impl Highlight {
@@ -108,8 +232,30 @@ impl Highlight {
             line,
             column,
             length,
+            end: None,
             note: None,
-            level: ErrorLevel::Error,
+            level: Severity::Error,
+        }
+    }
+
+    /// Create a new highlight spanning multiple lines, from the start position up to the end
+    /// column (exclusive) on the end line. It is drawn as a bracket on the left of the lines
+    /// with the note displayed at the end. If the start and end line are the same this results
+    /// in a normal highlight.
+    /// ```
+    /// use custom_error::*;
+    /// let context = Context::lines(vec!["key = \"abc", "def", "ghi\""])
+    ///     .linenumber(12)
+    ///     .highlight(Highlight::multiline(0, 6, 2, 4).note("this string is never terminated"));
+    /// ```
+    pub fn multiline(line: usize, column: usize, end_line: usize, end_column: usize) -> Self {
+        if end_line <= line {
+            Self::new(line, column, end_column.saturating_sub(column))
+        } else {
+            Self {
+                end: Some((end_line, end_column)),
+                ..Self::new(line, column, 0)
+            }
         }
     }
 
@@ -124,7 +270,7 @@ impl Highlight {
     /// Make this error into a warning.
     pub fn warning(self) -> Self {
         Self {
-            level: ErrorLevel::Warning,
+            level: Severity::Warning,
             ..self
         }
     }
@@ -132,12 +278,55 @@ impl Highlight {
     /// Make this error into an information message
     pub fn info(self) -> Self {
         Self {
-            level: ErrorLevel::Info,
+            level: Severity::Info,
             ..self
         }
     }
 }
 
+/// The serialized form of a highlight, every highlight is stored as a span from a start to an end
+/// position, to have the same shape for single and multi line highlights.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct HighlightJson {
+    /// The line offset in the list of lines for a context
+    line: usize,
+    column: usize,
+    end_line: usize,
+    /// The end column (exclusive)
+    end_column: usize,
+    note: Option<String>,
+    level: Severity,
+}
+
+#[cfg(feature = "serde")]
+impl From<Highlight> for HighlightJson {
+    fn from(highlight: Highlight) -> Self {
+        let (end_line, end_column) = highlight
+            .end
+            .unwrap_or((highlight.line, highlight.column + highlight.length));
+        Self {
+            line: highlight.line,
+            column: highlight.column,
+            end_line,
+            end_column,
+            note: highlight.note,
+            level: highlight.level,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<HighlightJson> for Highlight {
+    fn from(json: HighlightJson) -> Self {
+        Self {
+            note: json.note,
+            level: json.level,
+            ..Highlight::multiline(json.line, json.column, json.end_line, json.end_column)
+        }
+    }
+}
+
 impl From<(usize, usize, usize)> for Highlight {
     fn from(tuple: (usize, usize, usize)) -> Self {
         Highlight::new(tuple.0, tuple.1, tuple.2)
@@ -150,84 +339,33 @@ impl From<(usize, usize)> for Highlight {
     }
 }
 
-impl Display for Context {
-    fn fmt(&self, f: &mut Formatter) -> Result {
-        // Determine how many chars are needed to display the biggest line number, default is 1 to have at least 1 character
-        let linenumber_padding = ((self.linenumber.unwrap_or(1) + self.lines.len()) as f64)
-            .log10()
-            .ceil() as usize;
-
-        if let Some(file) = &self.file {
-            if self.highlights.len() == 1 {
-                // Show the filename and location of the highlight (if there is only one)
-                writeln!(
-                    f,
-                    "{:pad$} {}[{}{}]",
-                    "",
-                    blue("╭──"),
-                    file,
-                    self.linenumber // Show the linenumber followed by the column if the linenumber is known
-                        .map(|l| {
-                            let highlight = &self.highlights[0];
-                            format!(":{}:{}", l + highlight.line, highlight.column)
-                        })
-                        .unwrap_or_else(|| "".to_string()),
-                    pad = linenumber_padding
-                )?;
-            } else {
-                // If there are no or multiple highlights only show the filename
-                writeln!(
-                    f,
-                    "{:pad$} {}[{}]",
-                    "",
-                    blue("╭──"),
-                    file,
-                    pad = linenumber_padding
-                )?;
-            }
-            // Extend the sideline so that it provides a single line of border between the file header and content
-            writeln!(f, "{:pad$} {}", "", blue("│"), pad = linenumber_padding)?;
-        } else {
-            // If there is no file known just end the sideline nicely
-            writeln!(f, "{:pad$} {}", "", blue("╷"), pad = linenumber_padding)?;
+impl Context {
+    /// Get a structured view of this context, as used by a [Renderer](crate::Renderer).
+    pub fn view(&self) -> ContextView<'_> {
+        ContextView {
+            lines: &self.lines,
+            linenumber: self.linenumber,
+            file: self.file.as_deref(),
+            highlights: self
+                .highlights
+                .iter()
+                .map(|highlight| HighlightView {
+                    line: highlight.line,
+                    column: highlight.column,
+                    length: highlight.length,
+                    end: highlight.end,
+                    note: highlight.note.as_deref(),
+                    level: highlight.level,
+                })
+                .collect(),
+            unit: self.unit,
+            tab_width: self.tab_width,
         }
+    }
+}
 
-        // Use offset numbers if there is no linenumber given
-        let linenumber = self.linenumber.unwrap_or(0);
-        for (index, line) in self.lines.iter().enumerate() {
-            // Write the current line
-            writeln!(
-                f,
-                "{:>pad$} {} {}",
-                grey((linenumber + index).to_string()),
-                blue("│"),
-                line,
-                pad = linenumber_padding,
-            )?;
-            // Determine if there needs to be a highlight
-            for highlight in &self.highlights {
-                if index == highlight.line {
-                    writeln!(
-                        f,
-                        "{:>pad$} {} {}{}{}",
-                        "",
-                        blue("·"),
-                        " ".repeat(highlight.column),
-                        highlight.level.in_colour("─".repeat(highlight.length)),
-                        highlight.level.in_colour(
-                            highlight
-                                .note
-                                .as_ref()
-                                .map(|n| " ".to_string() + n)
-                                .unwrap_or_else(|| "".to_string())
-                        ),
-                        pad = linenumber_padding,
-                    )?;
-                }
-            }
-        }
-        // Nicely end the sideline
-        writeln!(f, "{:pad$} {}", "", blue("╵"), pad = linenumber_padding)?;
-        Ok(())
+impl Display for Context {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        DefaultRenderer::new().render_context(&self.view(), f)
     }
 }
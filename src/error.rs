@@ -1,40 +1,104 @@
+use crate::code::ErrorCode;
 use crate::colour::*;
 use crate::context::Context;
+use crate::render::*;
+use crate::suggestion::Suggestion;
 use std::convert::From;
 use std::error::Error;
 use std::fmt::Debug;
 use std::fmt::{Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::io::IsTerminal;
+use std::sync::Arc;
 
-/// To define an error level, is only used internally in this file
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-pub(crate) enum ErrorLevel {
-    Error,
-    Warning,
+/// The severity (or level) of an error, which determines how it is displayed. Severities are
+/// ordered from the least severe ([Severity::Hint]) to the most severe ([Severity::Bug]).
+/// ```
+/// use custom_error::*;
+/// assert!(Severity::Note < Severity::Warning);
+/// assert!(Severity::Fatal > Severity::Error);
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    /// A hint, for example about a style that could be improved
+    Hint,
+    /// A help message, for example on how to fix an earlier error
+    Help,
+    /// A note, for example pointing to extra information for an earlier error
+    Note,
+    /// An informational message
     Info,
+    /// A warning
+    Warning,
+    /// An error
+    Error,
+    /// An error after which processing cannot continue
+    Fatal,
+    /// An internal error, caused by a bug in the program itself
+    Bug,
 }
 
-impl ErrorLevel {
+impl Severity {
+    /// The name of this severity, as displayed without colours
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Hint => "hint",
+            Severity::Help => "help",
+            Severity::Note => "note",
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Fatal => "fatal",
+            Severity::Bug => "bug",
+        }
+    }
+
+    /// The name of this severity for multiple messages, as displayed without colours
+    pub fn plural(self) -> &'static str {
+        match self {
+            Severity::Hint => "hints",
+            Severity::Help => "help messages",
+            Severity::Note => "notes",
+            Severity::Info => "info messages",
+            Severity::Warning => "warnings",
+            Severity::Error => "errors",
+            Severity::Fatal => "fatal errors",
+            Severity::Bug => "bugs",
+        }
+    }
+
+    /// All severities, from the least severe to the most severe
+    pub fn all() -> [Severity; 8] {
+        [
+            Severity::Hint,
+            Severity::Help,
+            Severity::Note,
+            Severity::Info,
+            Severity::Warning,
+            Severity::Error,
+            Severity::Fatal,
+            Severity::Bug,
+        ]
+    }
+
+    /// Colour the given text in the colour of this severity
     pub fn in_colour(self, text: impl Into<String>) -> String {
         match self {
-            ErrorLevel::Error => red(text),
-            ErrorLevel::Warning => yellow(text),
-            ErrorLevel::Info => blue(text),
+            Severity::Hint => grey(text),
+            Severity::Help => cyan(text),
+            Severity::Note => green(text),
+            Severity::Info => blue(text),
+            Severity::Warning => yellow(text),
+            Severity::Error | Severity::Fatal | Severity::Bug => red(text),
         }
     }
 }
 
-impl Display for ErrorLevel {
+impl Display for Severity {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                ErrorLevel::Error => red("error"),
-                ErrorLevel::Warning => yellow("warning"),
-                ErrorLevel::Info => blue("info"),
-            },
-        )?;
-        Ok(())
+        write!(f, "{}", self.in_colour(self.name()))
     }
 }
 
@@ -59,31 +123,133 @@ impl Display for ErrorLevel {
 /// }
 /// ```
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CustomError<T> {
     kind: T,
-    level: ErrorLevel,
+    code: Option<String>,
+    level: Severity,
     title: Option<String>,
     message: Option<String>,
     help: Option<String>,
     url: Option<String>,
+    #[cfg_attr(feature = "serde", serde(rename = "contexts"))]
     context: Vec<Context>,
     location: Option<String>,
+    suggestions: Vec<Suggestion>,
+    children: Vec<Child>,
+    #[cfg_attr(feature = "serde", serde(rename = "causes"))]
+    source: Option<Cause>,
+}
+
+/// A message displayed beneath an error, like a note pointing to an earlier definition.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Child {
+    level: Severity,
+    message: String,
+    context: Option<Context>,
+}
+
+/// The cause of an error, shared so that the error can still be cloned. Causes are compared and
+/// hashed based on their messages.
+#[derive(Clone)]
+struct Cause(Arc<dyn Error + Send + Sync + 'static>);
+
+// A cause is never changed after it is added, it is only read to display it. So a panic cannot
+// leave it in a state that is observed later, which keeps errors usable in `catch_unwind`. Requiring
+// `RefUnwindSafe` of the cause instead would rule out common causes like [std::io::Error].
+impl std::panic::UnwindSafe for Cause {}
+impl std::panic::RefUnwindSafe for Cause {}
+
+impl Debug for Cause {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl PartialEq for Cause {
+    fn eq(&self, other: &Self) -> bool {
+        cause_messages(&*self.0) == cause_messages(&*other.0)
+    }
+}
+
+impl Eq for Cause {}
+
+impl Hash for Cause {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        cause_messages(&*self.0).hash(state)
+    }
+}
+
+/// The messages of the given error and all errors in its chain of sources
+fn cause_messages(cause: &(dyn Error + 'static)) -> Vec<String> {
+    cause_chain(Some(cause)).map(render_cause).collect()
+}
+
+/// A cause read back from its message, when deserialized
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+struct MessageError {
+    message: String,
+    source: Option<Box<MessageError>>,
+}
+
+#[cfg(feature = "serde")]
+impl Display for MessageError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for MessageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn Error + 'static))
+    }
+}
+
+/// A cause is stored as the list of messages of the whole chain of sources
+#[cfg(feature = "serde")]
+impl serde::Serialize for Cause {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        cause_messages(&*self.0).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Cause {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let messages = Vec::<String>::deserialize(deserializer)?;
+        let chain = messages
+            .into_iter()
+            .rev()
+            .fold(None, |source, message| {
+                Some(Box::new(MessageError { message, source }))
+            })
+            .ok_or_else(|| serde::de::Error::invalid_length(0, &"at least one cause"))?;
+        Ok(Cause(Arc::new(*chain)))
+    }
 }
 
 /// The functionality useful for creation of a CustomError
 impl<T> CustomError<T> {
     /// Create a new error with the given type. It will be classified as an error
-    /// (not a warning or info message).
+    /// (not a warning or info message). This does not use the code or defaults of an [ErrorCode]
+    /// implementation, even if the kind has one, use [CustomError::coded] for that instead.
     pub fn new(kind: T) -> Self {
         CustomError {
             kind,
-            level: ErrorLevel::Error,
+            code: None,
+            level: Severity::Error,
             title: None,
             message: None,
             help: None,
             url: None,
             context: Vec::new(),
             location: None,
+            suggestions: Vec::new(),
+            children: Vec::new(),
+            source: None,
         }
     }
 
@@ -136,10 +302,32 @@ impl<T> CustomError<T> {
         self
     }
 
+    /// Set the file of all contexts of this error (including the contexts of its children) that
+    /// do not have a file yet, for example when the file is only known in an outer function.
+    pub fn file(self, file: impl Into<String>) -> Self {
+        let file = file.into();
+        CustomError {
+            context: self
+                .context
+                .into_iter()
+                .map(|context| context.default_file(&file))
+                .collect(),
+            children: self
+                .children
+                .into_iter()
+                .map(|child| Child {
+                    context: child.context.map(|context| context.default_file(&file)),
+                    ..child
+                })
+                .collect(),
+            ..self
+        }
+    }
+
     /// Make this error into a warning.
     pub fn warning(self) -> Self {
         CustomError {
-            level: ErrorLevel::Warning,
+            level: Severity::Warning,
             ..self
         }
     }
@@ -147,7 +335,74 @@ impl<T> CustomError<T> {
     /// Make this error into an information message
     pub fn info(self) -> Self {
         CustomError {
-            level: ErrorLevel::Info,
+            level: Severity::Info,
+            ..self
+        }
+    }
+
+    /// Add a suggestion to fix this error, displayed as a preview of the changed lines. Calling
+    /// this multiple times adds all suggestions. See [apply_suggestions](crate::apply_suggestions)
+    /// to apply them.
+    pub fn suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
+    /// Add a message with its own level and optionally its own context, which is displayed beneath
+    /// this error. Calling this multiple times adds all messages, which are displayed in order.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     DuplicateDefinition,
+    /// }
+    /// let source = Source::new("defs.txt", "let a = 1;\nlet a = 2;\n");
+    /// let error = CustomError::new(ErrorType::DuplicateDefinition)
+    ///     .context(Context::from_span(&source, 15..16).note("redefined here"))
+    ///     .child(Severity::Info, "previous definition here", Some(Context::from_span(&source, 4..5)))
+    ///     .child(Severity::Info, "remove one of the definitions", None);
+    /// let output = error.with_colour(ColourChoice::Never).to_string();
+    /// let previous = output.find("info: previous definition here").unwrap();
+    /// assert!(output.find("redefined here").unwrap() < previous);
+    /// assert!(output[previous..].contains("1 │ let a = 1;"));
+    /// assert!(output.ends_with("info: remove one of the definitions\n"));
+    /// ```
+    pub fn child(
+        mut self,
+        level: Severity,
+        message: impl Into<String>,
+        context: Option<Context>,
+    ) -> Self {
+        self.children.push(Child {
+            level,
+            message: message.into(),
+            context,
+        });
+        self
+    }
+
+    /// Add the underlying error that caused this error, like an [std::io::Error] or a
+    /// [std::num::ParseIntError]. It is returned by [Error::source] and the whole chain of causes
+    /// is displayed after the error. The cause can itself be a [CustomError] with its own context.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let error = CustomError::new(ErrorType::NotANumber)
+    ///     .source("12e".parse::<usize>().unwrap_err());
+    /// // Because of this method `error.source()` does not call the method from [std::error::Error]
+    /// assert!(std::error::Error::source(&error).is_some());
+    /// assert!(error.with_colour(ColourChoice::Never).to_string().contains("caused by: invalid digit found in string"));
+    ///
+    /// // Errors with a cause can still be used in `catch_unwind`
+    /// let error = error.source(std::io::Error::other("file not found"));
+    /// assert!(std::panic::catch_unwind(|| error.to_string()).is_ok());
+    /// ```
+    pub fn source(self, source: impl Error + Send + Sync + 'static) -> Self {
+        CustomError {
+            source: Some(Cause(Arc::new(source))),
             ..self
         }
     }
@@ -162,6 +417,37 @@ impl<T> CustomError<T> {
     }
 }
 
+impl<T: ErrorCode> CustomError<T> {
+    /// Create a new error with the given type, with the code, title, help, url and level given by
+    /// its [ErrorCode] implementation. If no level is given it will be classified as an error (not
+    /// a warning or info message). Use `CustomError!(kind, coded)` to also annotate the location.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// impl ErrorCode for ErrorType {
+    ///     fn code(&self) -> &'static str {
+    ///         "P0001"
+    ///     }
+    /// }
+    /// assert_eq!(CustomError!(ErrorType::NotANumber, coded).code(), Some("P0001"));
+    /// // `new` does not use the ErrorCode implementation
+    /// assert_eq!(CustomError::new(ErrorType::NotANumber).code(), None);
+    /// ```
+    pub fn coded(kind: T) -> Self {
+        CustomError {
+            code: Some(kind.code().to_string()),
+            level: kind.level().unwrap_or(Severity::Error),
+            title: kind.title().map(str::to_string),
+            help: kind.help().map(str::to_string),
+            url: kind.url(),
+            ..CustomError::new(kind)
+        }
+    }
+}
+
 impl<T: Debug> CustomError<T> {
     /// Should not be used by end users, use the macro instead [CustomError!]
     #[doc(hidden)]
@@ -240,6 +526,7 @@ impl<T> CustomError<T> {
     pub fn convert<O: From<T>>(self) -> CustomError<O> {
         CustomError {
             kind: self.kind.into(),
+            code: self.code,
             level: self.level,
             title: self.title,
             message: self.message,
@@ -247,6 +534,9 @@ impl<T> CustomError<T> {
             url: self.url,
             context: self.context,
             location: self.location,
+            suggestions: self.suggestions,
+            children: self.children,
+            source: self.source,
         }
     }
 
@@ -256,19 +546,78 @@ impl<T> CustomError<T> {
         &self.kind
     }
 
-    /// Test if this error is flagged as an error.
+    /// Get the code of this error, only available if created with [CustomError::coded].
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Get all suggestions to fix this error, see [CustomError::suggestion].
+    pub fn suggestions(&self) -> &[Suggestion] {
+        &self.suggestions
+    }
+
+    /// Get the level of this error.
+    pub fn level(&self) -> Severity {
+        self.level
+    }
+
+    /// Change the level of this error, for example to promote a warning to an error.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     UnusedValue,
+    /// }
+    /// let mut error = CustomError::new(ErrorType::UnusedValue);
+    /// error.set_level(Severity::Note);
+    /// assert_eq!(error.level(), Severity::Note);
+    /// assert!(error.with_colour(ColourChoice::Never).to_string().starts_with("note: "));
+    /// ```
+    pub fn set_level(&mut self, level: Severity) {
+        self.level = level;
+    }
+
+    /// Get the position of this error, the file, linenumber and column of the first highlight of
+    /// the first context (or the start of the context if it has no highlights). The column is
+    /// counted in chars, whatever the unit of the context.
+    pub(crate) fn position(&self) -> Option<(Option<&str>, usize, usize)> {
+        let context = self.context.first()?.view();
+        let (line, column) = context.highlights.first().map_or((0, 0), |highlight| {
+            (
+                highlight.line,
+                context.char_column(highlight.line, highlight.column),
+            )
+        });
+        Some((context.file, context.linenumber.unwrap_or(0) + line, column))
+    }
+
+    /// Test if this error is flagged as an error, or as something more severe (fatal or a bug).
     pub fn is_error(&self) -> bool {
-        self.level == ErrorLevel::Error
+        self.level >= Severity::Error
     }
 
     /// Test if this error is flagged as a warning.
     pub fn is_warning(&self) -> bool {
-        self.level == ErrorLevel::Warning
+        self.level == Severity::Warning
     }
 
     /// Test if this error is flagged as an informational message.
     pub fn is_info(&self) -> bool {
-        self.level == ErrorLevel::Info
+        self.level == Severity::Info
+    }
+
+    /// Display this error with the given colour choice, instead of the global choice.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let error = CustomError::new(ErrorType::NotANumber);
+    /// assert!(!error.with_colour(ColourChoice::Never).to_string().contains('\u{1b}'));
+    /// ```
+    pub fn with_colour(&self, choice: ColourChoice) -> WithColour<'_, Self> {
+        WithColour::new(self, choice)
     }
 }
 
@@ -287,50 +636,95 @@ macro_rules! CustomError {
             .location(format!("{}:{}:{}", file!(), line!(), column!()))
             .docs_link(module_path!(), env!("CARGO_PKG_VERSION"))
     };
+    // Create a [CustomError] with the location of the code generating this error and the code and defaults of its [ErrorCode] implementation
+    ($kind:expr, coded) => {
+        CustomError::coded($kind).location(format!("{}:{}:{}", file!(), line!(), column!()))
+    };
+}
+
+impl<T: Debug> CustomError<T> {
+    /// Get a structured view of this error, as used by a [Renderer].
+    pub fn view(&self) -> ErrorView<'_> {
+        ErrorView {
+            level: self.level,
+            kind: format!("{}::{:?}", std::any::type_name::<T>(), self.kind),
+            code: self.code.as_deref(),
+            title: self.title.as_deref(),
+            message: self.message.as_deref(),
+            help: self.help.as_deref(),
+            url: self.url.as_deref(),
+            location: self.location.as_deref(),
+            contexts: self.context.iter().map(Context::view).collect(),
+            suggestions: self.suggestions.iter().map(Suggestion::view).collect(),
+            collapsed: 0,
+            children: self
+                .children
+                .iter()
+                .map(|child| ChildView {
+                    level: child.level,
+                    message: &child.message,
+                    context: child.context.as_ref().map(Context::view),
+                })
+                .collect(),
+            causes: if rendering_cause() {
+                // The chain is already displayed by the error this is the cause of
+                Vec::new()
+            } else {
+                cause_chain(Error::source(self)).collect()
+            },
+        }
+    }
+
+    /// Render this error with the given renderer.
+    pub fn render(
+        &self,
+        renderer: &dyn Renderer,
+        out: &mut impl std::fmt::Write,
+    ) -> std::fmt::Result {
+        scoped(None, || renderer.render_error(&self.view(), out))
+    }
+
+    /// Render this error with the given renderer and write it to the given writer, for example stderr.
+    /// With [ColourChoice::Auto] colours are used if the writer is a terminal, use
+    /// [CustomError::render] for other writers.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let error = CustomError::new(ErrorType::NotANumber);
+    /// error.write_to(&DefaultRenderer::new(), &mut std::io::stderr()).unwrap();
+    /// ```
+    pub fn write_to(
+        &self,
+        renderer: &dyn Renderer,
+        out: &mut (impl std::io::Write + IsTerminal),
+    ) -> std::io::Result<()> {
+        let mut output = String::new();
+        scoped_for(None, out, || self.render(renderer, &mut output))
+            .map_err(|_| std::io::Error::other("could not render the error"))?;
+        out.write_all(output.as_bytes())
+    }
 }
 
 impl<T: Debug> Display for CustomError<T> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        if let Some(title) = &self.title {
-            writeln!(
-                f,
-                "{}: {} ({}::{:?})",
-                self.level,
-                title,
-                std::any::type_name::<T>(),
-                self.kind,
-            )?;
-        } else {
-            writeln!(
-                f,
-                "{}: {}::{:?}",
-                self.level,
-                std::any::type_name::<T>(),
-                self.kind,
-            )?;
-        }
-        if let Some(url) = &self.url {
-            writeln!(f, "{}: {}", blue("url"), blue(url))?;
-        } //┅┅┅┅ ┉┉┉┉┉┉ ┗━━━━━━┛ ╍╍╍╍╍╍ ══════════ ╰────╯╭
-        if let Some(location) = &self.location {
-            writeln!(f, "  {} generated at: {}", blue("-->"), location)?;
-        }
-        for context in &self.context {
-            write!(f, "{}", context)?;
-        }
-        if let Some(message) = &self.message {
-            writeln!(f, "{}", message)?;
-        }
-        if let Some(help) = &self.help {
-            writeln!(f, "  {}: {}", blue("help"), help)?;
-        }
-        Ok(())
+        self.render(&DefaultRenderer::new(), f)
     }
 }
 
-impl<T: Debug> Error for CustomError<T> {}
+impl<T: Debug> Error for CustomError<T> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|cause| &*cause.0 as &(dyn Error + 'static))
+    }
+}
 
 /// A trait to help with creating Custom Errors from structs that are normally used with .unwrap().
+// Part of the original API, boxing the returned error would break existing users
+#[allow(clippy::result_large_err)]
 pub trait CustomErrorUnwrap<T> {
     /// Use this to create a new error message based on a type normally unwrapped.
     /// ```
@@ -348,6 +742,7 @@ pub trait CustomErrorUnwrap<T> {
 }
 
 /// A trait to help with creating Custom Errors from structs that are normally used with .unwrap().
+#[allow(clippy::result_large_err)]
 pub trait CustomErrorFnUnwrap<T, R> {
     /// Use this to create a new error message based on a type normally unwrapped.
     /// ```
@@ -357,7 +752,7 @@ pub trait CustomErrorFnUnwrap<T, R> {
     /// }
     /// fn test() -> Result<(), CustomError<ErrorType>> {
     ///     let a: Result<usize, _> = "12e".parse();
-    ///     a.unwrap_or_error_fn(|e| CustomError::new(ErrorType::NotANumber).message(e.to_string()))?; // Use '?' to propagate the error
+    ///     a.unwrap_or_error_fn(|e| CustomError::new(ErrorType::NotANumber).source(e))?; // Use '?' to propagate the error
     ///     Ok(())
     /// }
     /// ```
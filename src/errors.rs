@@ -1,8 +1,12 @@
 use crate::colour::*;
-use crate::error::CustomError;
+use crate::error::{CustomError, Severity};
+use crate::policy::RenderPolicy;
+use crate::render::*;
+use std::collections::BTreeMap;
 use std::convert::From;
 use std::fmt::Debug;
 use std::fmt::{Display, Formatter, Result};
+use std::io::IsTerminal;
 
 /// A collection of errors of the given type with convenience methods for handling.
 ///
@@ -25,14 +29,60 @@ use std::fmt::{Display, Formatter, Result};
 /// }
 /// ```
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CustomErrors<T> {
     errors: Vec<CustomError<T>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    policy: RenderPolicy,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    limits: Limits,
+    /// The number of errors left out because a limit was reached
+    #[cfg_attr(feature = "serde", serde(skip))]
+    suppressed: usize,
+}
+
+/// The maximum number of errors kept in a collection, in total and for every level
+#[derive(Debug, Clone, Default)]
+struct Limits {
+    total: Option<usize>,
+    /// The maximum and the number of kept errors for every level with a limit
+    levels: BTreeMap<Severity, (usize, usize)>,
+    /// If the numbers of kept errors in `levels` are up to date
+    counted: bool,
+}
+
+impl Limits {
+    /// The maximum number of errors for every level with a limit
+    fn maxima(&self) -> impl Iterator<Item = (&Severity, &usize)> {
+        self.levels.iter().map(|(level, (max, _))| (level, max))
+    }
+}
+
+// The counts follow from the errors, so they are left out when comparing
+impl PartialEq for Limits {
+    fn eq(&self, other: &Self) -> bool {
+        self.total == other.total && self.maxima().eq(other.maxima())
+    }
+}
+
+impl Eq for Limits {}
+
+impl std::hash::Hash for Limits {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.total.hash(state);
+        self.maxima().for_each(|maximum| maximum.hash(state));
+    }
 }
 
 impl<T> CustomErrors<T> {
     /// Create an empty set of errors.
     pub fn new() -> Self {
-        CustomErrors { errors: Vec::new() }
+        CustomErrors {
+            errors: Vec::new(),
+            policy: RenderPolicy::new(),
+            limits: Limits::default(),
+            suppressed: 0,
+        }
     }
 
     /// Check if there are any errors, warnings, or information messages in this collection.
@@ -50,9 +100,253 @@ impl<T> CustomErrors<T> {
         self.errors.iter().any(|e| e.is_error() || e.is_warning())
     }
 
-    /// Add an extra error to the collection
-    pub fn push(&mut self, error: CustomError<T>) {
-        self.errors.push(error);
+    /// Return the given value if there are no errors (warnings and other messages are allowed) in
+    /// this collection, otherwise return this collection. Note that the warnings and other
+    /// messages are lost if there are no errors, so display them first if they should be shown.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug, Clone)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    ///     UnusedValue,
+    /// }
+    /// let mut errors = CustomErrors::new();
+    /// errors += CustomError::new(ErrorType::UnusedValue).warning();
+    /// assert_eq!(errors.clone().into_result(42).unwrap(), 42);
+    /// errors += CustomError::new(ErrorType::NotANumber);
+    /// assert!(errors.into_result(42).is_err());
+    /// ```
+    pub fn into_result<V>(self, value: V) -> std::result::Result<V, Self> {
+        if self.any_errors() {
+            Err(self)
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// Set the policy used to reduce the errors when rendering, for example to leave out duplicates.
+    pub fn policy(self, policy: RenderPolicy) -> Self {
+        CustomErrors { policy, ..self }
+    }
+
+    /// Only keep the first `max` messages (of any level) added to this collection, all later
+    /// messages are suppressed. See [CustomErrors::push].
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let mut errors = CustomErrors::new().limit(2);
+    /// assert!(!errors.push(CustomError::new(ErrorType::NotANumber)));
+    /// assert!(errors.push(CustomError::new(ErrorType::NotANumber))); // The limit is reached, stop parsing
+    /// assert!(errors.push(CustomError::new(ErrorType::NotANumber)));
+    /// assert_eq!(errors.iter().count(), 2);
+    /// assert_eq!(errors.suppressed(), 1);
+    /// assert!(errors.with_colour(ColourChoice::Never).to_string().contains("1 more suppressed"));
+    /// ```
+    pub fn limit(mut self, max: usize) -> Self {
+        self.limits.total = Some(max);
+        self
+    }
+
+    /// Only keep the first `max` messages of the given level added to this collection, all later
+    /// messages of that level are suppressed. Calling this for multiple levels sets a limit for
+    /// every level.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     UnusedValue,
+    /// }
+    /// let mut errors = CustomErrors::new().level_limit(Severity::Warning, 1);
+    /// assert!(errors.push(CustomError::new(ErrorType::UnusedValue).warning()));
+    /// assert!(!errors.push(CustomError::new(ErrorType::UnusedValue)));
+    /// // Removing errors makes room for new ones
+    /// errors.filter_level(Severity::Error);
+    /// assert!(errors.push(CustomError::new(ErrorType::UnusedValue).warning()));
+    /// assert_eq!(errors.suppressed(), 0);
+    /// ```
+    pub fn level_limit(mut self, level: Severity, max: usize) -> Self {
+        self.limits.levels.insert(level, (max, 0));
+        self.limits.counted = false;
+        self
+    }
+
+    /// Add an extra error to the collection. If a limit for this collection was already reached
+    /// the error is suppressed instead. Returns true if a limit that applies to the error is
+    /// reached, so that later errors of the same level will be suppressed and the caller can stop.
+    pub fn push(&mut self, error: CustomError<T>) -> bool {
+        let level = error.level();
+        if self.limit_reached(level) {
+            self.suppressed += 1;
+        } else {
+            self.errors.push(error);
+            if let (true, Some((_, count))) =
+                (self.limits.counted, self.limits.levels.get_mut(&level))
+            {
+                *count += 1;
+            }
+        }
+        self.limit_reached(level)
+    }
+
+    /// Check if a limit that applies to messages of the given level is reached
+    fn limit_reached(&mut self, level: Severity) -> bool {
+        if self
+            .limits
+            .total
+            .is_some_and(|max| self.errors.len() >= max)
+        {
+            return true;
+        }
+        if !self.limits.levels.contains_key(&level) {
+            return false;
+        }
+        self.count_levels();
+        let (max, count) = self.limits.levels[&level];
+        count >= max
+    }
+
+    /// Count the kept errors for every level with a limit, if they are not up to date
+    fn count_levels(&mut self) {
+        if self.limits.counted {
+            return;
+        }
+        for (_, count) in self.limits.levels.values_mut() {
+            *count = 0;
+        }
+        for error in &self.errors {
+            if let Some((_, count)) = self.limits.levels.get_mut(&error.level()) {
+                *count += 1;
+            }
+        }
+        self.limits.counted = true;
+    }
+
+    /// The number of errors that were suppressed because a limit was reached.
+    pub fn suppressed(&self) -> usize {
+        self.suppressed
+    }
+
+    /// Change every error in this collection with the given function.
+    pub(crate) fn map(mut self, f: impl FnMut(CustomError<T>) -> CustomError<T>) -> Self {
+        // The levels of the errors can be changed
+        self.limits.counted = false;
+        CustomErrors {
+            errors: self.errors.into_iter().map(f).collect(),
+            ..self
+        }
+    }
+
+    /// Take all errors out of this collection, the policy and limits are kept in both.
+    pub(crate) fn take(&mut self) -> Self {
+        let limits = self.limits.clone();
+        self.limits.counted = false;
+        CustomErrors {
+            errors: std::mem::take(&mut self.errors),
+            policy: self.policy,
+            limits,
+            suppressed: std::mem::take(&mut self.suppressed),
+        }
+    }
+
+    /// Only keep the errors for which the given function returns true.
+    pub fn retain(&mut self, mut keep: impl FnMut(&CustomError<T>) -> bool) {
+        let limits = &mut self.limits;
+        self.errors.retain(|error| {
+            let kept = keep(error);
+            if let (false, true, Some((_, count))) =
+                (kept, limits.counted, limits.levels.get_mut(&error.level()))
+            {
+                *count -= 1;
+            }
+            kept
+        });
+    }
+
+    /// Only keep the errors with at least the given level, for example only errors for a `--quiet` mode.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    ///     UnusedValue,
+    /// }
+    /// let mut errors = CustomErrors::new();
+    /// errors += CustomError::new(ErrorType::UnusedValue).warning();
+    /// errors += CustomError::new(ErrorType::NotANumber);
+    /// errors.filter_level(Severity::Error);
+    /// assert!(errors.iter().all(|e| e.is_error()));
+    /// ```
+    pub fn filter_level(&mut self, min_level: Severity) {
+        self.retain(|e| e.level() >= min_level);
+    }
+
+    /// Only keep the errors for which the given function returns true for their kind.
+    pub fn filter_kind(&mut self, mut keep: impl FnMut(&T) -> bool) {
+        self.retain(|e| keep(e.kind()));
+    }
+
+    /// Sort the errors on their position: the file, linenumber and column of the first context.
+    /// Errors without a file are placed after errors with a file and errors without a context
+    /// are placed last. Errors with the same position stay in the order they were added.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug, PartialEq)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    ///     UnusedValue,
+    /// }
+    /// let source = Source::new("numbers.txt", "help 123\nhelp l23\n");
+    /// let mut errors = CustomErrors::new();
+    /// errors += CustomError::new(ErrorType::NotANumber).context(Context::from_span(&source, 14..17));
+    /// errors += CustomError::new(ErrorType::UnusedValue).context(Context::from_span(&source, 5..8));
+    /// errors.sort_by_location();
+    /// assert_eq!(errors.iter().next().unwrap().kind(), &ErrorType::UnusedValue);
+    ///
+    /// // Columns are compared in chars, whatever the unit of the context
+    /// let line = "ïïï = l23";
+    /// let mut errors = CustomErrors::new();
+    /// errors += CustomError::new(ErrorType::UnusedValue).context(Context::line(line).highlight((7, 1)));
+    /// // Byte 9 is char 6
+    /// errors += CustomError::new(ErrorType::NotANumber)
+    ///     .context(Context::line(line).unit(OffsetUnit::Byte).highlight((9, 3)));
+    /// errors.sort_by_location();
+    /// assert_eq!(errors.iter().next().unwrap().kind(), &ErrorType::NotANumber);
+    /// ```
+    pub fn sort_by_location(&mut self) {
+        self.errors.sort_by(|a, b| {
+            let (a, b) = (a.position(), b.position());
+            let missing = |p: Option<(Option<&str>, _, _)>| (p.is_none(), p.map(|p| p.0.is_none()));
+            missing(a).cmp(&missing(b)).then(a.cmp(&b))
+        });
+    }
+
+    /// Split the errors in a collection for every level, the policy is kept for all collections.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    ///     UnusedValue,
+    /// }
+    /// let mut errors = CustomErrors::new();
+    /// errors += CustomError::new(ErrorType::UnusedValue).warning();
+    /// errors += CustomError::new(ErrorType::NotANumber);
+    /// let levels = errors.partition_by_level();
+    /// assert_eq!(levels[&Severity::Warning].iter().count(), 1);
+    /// assert!(!levels.contains_key(&Severity::Info));
+    /// ```
+    pub fn partition_by_level(self) -> BTreeMap<Severity, CustomErrors<T>> {
+        let mut output = BTreeMap::new();
+        for error in self.errors {
+            output
+                .entry(error.level())
+                .or_insert_with(|| CustomErrors::new().policy(self.policy))
+                .push(error);
+        }
+        output
     }
 
     /// Convert one error type to another, useful if you aggregate multiple error sources together.
@@ -64,18 +358,28 @@ impl<T> CustomErrors<T> {
     pub fn convert<O: From<T>>(self) -> CustomErrors<O> {
         CustomErrors {
             errors: self.errors.into_iter().map(|e| e.convert()).collect(),
+            policy: self.policy,
+            limits: self.limits,
+            suppressed: self.suppressed,
         }
     }
 
+    /// Display this collection with the given colour choice, instead of the global choice.
+    pub fn with_colour(&self, choice: ColourChoice) -> WithColour<'_, Self> {
+        WithColour::new(self, choice)
+    }
+
     /// Create an iterator over references of the errors in this collection.
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter {
             iter: Box::new(self.errors.iter()),
         }
     }
 
     /// Create an iterator over mutable references of the errors in this collection.
-    pub fn iter_mut(&mut self) -> IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        // The levels of the errors can be changed
+        self.limits.counted = false;
         IterMut {
             iter: Box::new(self.errors.iter_mut()),
         }
@@ -90,7 +394,7 @@ impl<T> Default for CustomErrors<T> {
 
 impl<T> std::ops::AddAssign<CustomError<T>> for CustomErrors<T> {
     fn add_assign(&mut self, rhs: CustomError<T>) {
-        self.errors.push(rhs);
+        self.push(rhs);
     }
 }
 
@@ -98,13 +402,16 @@ impl<T, C: Into<CustomError<T>>> std::iter::FromIterator<C> for CustomErrors<T>
     fn from_iter<I: IntoIterator<Item = C>>(iter: I) -> Self {
         CustomErrors {
             errors: iter.into_iter().map(|i| i.into()).collect(),
+            ..CustomErrors::new()
         }
     }
 }
 
 impl<T, C: Into<CustomError<T>>> std::iter::Extend<C> for CustomErrors<T> {
     fn extend<I: IntoIterator<Item = C>>(&mut self, iter: I) {
-        self.errors.extend(iter.into_iter().map(|item| item.into()))
+        for item in iter {
+            self.push(item.into());
+        }
     }
 }
 
@@ -138,39 +445,43 @@ impl<T> std::iter::IntoIterator for CustomErrors<T> {
     }
 }
 
+impl<T: Debug> CustomErrors<T> {
+    /// Render all errors in this collection with the given renderer, reduced by the policy of
+    /// this collection if the renderer applies it (see [Renderer::apply_policy]).
+    pub fn render(&self, renderer: &dyn Renderer, out: &mut impl std::fmt::Write) -> Result {
+        scoped(None, || {
+            let views = self.errors.iter().map(CustomError::view).collect();
+            let views = if renderer.apply_policy() {
+                self.policy.apply(views, renderer)
+            } else {
+                views
+            };
+            let summary = Summary {
+                suppressed: self.suppressed,
+                ..Summary::new(&views)
+            };
+            renderer.render_errors(&views, &summary, out)
+        })
+    }
+
+    /// Render all errors in this collection with the given renderer and write them to the given
+    /// writer, for example stderr. With [ColourChoice::Auto] colours are used if the writer is a
+    /// terminal, use [CustomErrors::render] for other writers.
+    pub fn write_to(
+        &self,
+        renderer: &dyn Renderer,
+        out: &mut (impl std::io::Write + IsTerminal),
+    ) -> std::io::Result<()> {
+        let mut output = String::new();
+        scoped_for(None, out, || self.render(renderer, &mut output))
+            .map_err(|_| std::io::Error::other("could not render the errors"))?;
+        out.write_all(output.as_bytes())
+    }
+}
+
 impl<T: Debug> Display for CustomErrors<T> {
     fn fmt(&self, f: &mut Formatter) -> Result {
-        let mut errors = 0;
-        let mut warnings = 0;
-        let mut infos = 0;
-        for error in &self.errors {
-            writeln!(f, "{}", error)?;
-            if error.is_error() {
-                errors += 1;
-            }
-            if error.is_warning() {
-                warnings += 1;
-            }
-            if error.is_info() {
-                infos += 1;
-            }
-        }
-        if errors + warnings + infos == 0 {
-            writeln!(f, "\n{}", green("no messages!"))?;
-        } else {
-            write!(f, "\nencountered: ")?;
-            if errors > 0 {
-                write!(f, "{} {}", errors, red("errors"))?;
-            }
-            if warnings > 0 {
-                write!(f, "{} {}", warnings, yellow("warnings"))?;
-            }
-            if infos > 0 {
-                write!(f, "{} {}", infos, blue("info messages"))?;
-            }
-        }
-
-        Ok(())
+        self.render(&DefaultRenderer::new(), f)
     }
 }
 
@@ -0,0 +1,168 @@
+use crate::code::{ErrorCode, ErrorKinds};
+use crate::colour::*;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result};
+
+/// A long form explanation for an error code, written in markdown. Displaying it renders the
+/// markdown for the terminal, with the same colour handling as displaying a [CustomError](struct@crate::CustomError).
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Explanation {
+    code: String,
+    title: Option<String>,
+    text: String,
+}
+
+impl Explanation {
+    /// The code this explanation is for
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// The title of the error, if given
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// The explanation itself, in markdown
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Display this explanation with the given colour choice, instead of the global choice.
+    pub fn with_colour(&self, choice: ColourChoice) -> WithColour<'_, Self> {
+        WithColour::new(self, choice)
+    }
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        scoped(None, || {
+            match &self.title {
+                Some(title) => writeln!(f, "{}: {}", blue(&self.code), title)?,
+                None => writeln!(f, "{}", blue(&self.code))?,
+            }
+            writeln!(f)?;
+            if self.text.trim().is_empty() {
+                return writeln!(f, "{}", grey("no explanation available"));
+            }
+            let mut code_block = false;
+            for line in self.text.lines() {
+                if line.trim_start().starts_with("```") {
+                    code_block = !code_block;
+                } else if code_block {
+                    writeln!(f, "{} {}", blue("│"), line)?;
+                } else if line.starts_with('#') {
+                    writeln!(f, "{}", blue(line.trim_start_matches('#').trim()))?;
+                } else {
+                    // Highlight inline code, which is every other piece between backticks
+                    for (index, piece) in line.split('`').enumerate() {
+                        if index % 2 == 1 {
+                            write!(f, "`{}`", yellow(piece))?;
+                        } else {
+                            write!(f, "{}", piece)?;
+                        }
+                    }
+                    writeln!(f)?;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+/// A registry of explanations for error codes, to implement a lookup like `rustc --explain E0308`.
+/// The explanations can be added one by one, or taken from the descriptions of an [ErrorCode]
+/// implementation (the doc comments if it is derived).
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ParseError {
+///     NotANumber,
+/// }
+///
+/// impl ErrorCode for ParseError {
+///     fn code(&self) -> &'static str {
+///         "P0001"
+///     }
+///
+///     fn title(&self) -> Option<&'static str> {
+///         Some("Not a number")
+///     }
+///
+///     fn description(&self) -> Option<&'static str> {
+///         Some("A number was expected but the value contains other characters than digits.\n```\nhelp l23\n```")
+///     }
+/// }
+///
+/// let registry = Registry::new()
+///     .kinds([ParseError::NotANumber])
+///     .explanation("P0002", Some("Missing help"), "Every line has to start with `help`.");
+/// assert_eq!(registry.codes().collect::<Vec<_>>(), vec![("P0001", Some("Not a number")), ("P0002", Some("Missing help"))]);
+/// if let Some(explanation) = registry.explain("P0001") {
+///     println!("{}", explanation);
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default)]
+pub struct Registry {
+    explanations: BTreeMap<String, Explanation>,
+}
+
+impl Registry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an explanation for the given code, this replaces any earlier explanation for this code.
+    pub fn explanation(
+        mut self,
+        code: impl Into<String>,
+        title: Option<&str>,
+        text: impl Into<String>,
+    ) -> Self {
+        let code = code.into();
+        self.explanations.insert(
+            code.clone(),
+            Explanation {
+                code,
+                title: title.map(str::to_string),
+                text: text.into(),
+            },
+        );
+        self
+    }
+
+    /// Add the explanations for the given kinds, using the code, title and description of their
+    /// [ErrorCode] implementation.
+    pub fn kinds<T: ErrorCode>(self, kinds: impl IntoIterator<Item = T>) -> Self {
+        kinds.into_iter().fold(self, |registry, kind| {
+            registry.explanation(
+                kind.code(),
+                kind.title(),
+                kind.description().unwrap_or_default(),
+            )
+        })
+    }
+
+    /// Add the explanations for all kinds of the given type, see [Registry::kinds].
+    pub fn all_kinds<T: ErrorKinds>(self) -> Self {
+        self.kinds(T::kinds())
+    }
+
+    /// Get the explanation for the given code
+    pub fn explain(&self, code: &str) -> Option<&Explanation> {
+        self.explanations.get(code)
+    }
+
+    /// Get the explanation for the code of the given kind
+    pub fn explain_kind(&self, kind: &impl ErrorCode) -> Option<&Explanation> {
+        self.explain(kind.code())
+    }
+
+    /// List all registered codes with their titles, sorted by code
+    pub fn codes(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.explanations
+            .values()
+            .map(|explanation| (explanation.code(), explanation.title()))
+    }
+}
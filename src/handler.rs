@@ -0,0 +1,253 @@
+use crate::colour::*;
+use crate::error::{CustomError, Severity};
+use crate::errors::CustomErrors;
+use crate::render::{DefaultRenderer, Renderer};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+thread_local! {
+    /// The handlers installed on this thread, the innermost last
+    static HANDLERS: RefCell<Vec<Arc<dyn Any + Send + Sync>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Collects the errors emitted with [CustomError::emit] while it is installed, so that the errors
+/// do not have to be passed through every function. The errors are kept until they are reported
+/// at the end, with the configured renderer and colour choice. A handler can be installed for a
+/// scope on the current thread, or shared with other threads in an [Arc] and used directly.
+/// ```
+/// use custom_error::*;
+/// use std::sync::Arc;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+///     UnusedValue,
+/// }
+///
+/// fn check(input: &str) {
+///     if input.parse::<usize>().is_err() {
+///         CustomError::new(ErrorType::NotANumber).context(Context::line(input)).emit();
+///     }
+/// }
+///
+/// let handler = Arc::new(DiagnosticHandler::<ErrorType>::new().colour(ColourChoice::Never));
+/// handler.install(|| {
+///     check("12");
+///     check("l2");
+///     CustomError::new(ErrorType::UnusedValue).warning().emit();
+/// });
+/// assert_eq!(handler.count(Severity::Error), 1);
+/// assert_eq!(handler.count(Severity::Warning), 1);
+/// assert!(handler.has_failed());
+/// let mut output = String::new();
+/// handler.render(&mut output).unwrap();
+/// assert!(output.ends_with("encountered: 1 errors, 1 warnings"));
+/// ```
+pub struct DiagnosticHandler<T> {
+    emitted: Mutex<Emitted<T>>,
+    renderer: Box<dyn Renderer + Send + Sync>,
+    colour: Option<ColourChoice>,
+}
+
+/// The errors emitted to a handler, kept behind a single lock so the counts always match
+struct Emitted<T> {
+    errors: CustomErrors<T>,
+    /// The number of emitted messages of every level, including suppressed messages
+    counts: BTreeMap<Severity, usize>,
+}
+
+impl<T> DiagnosticHandler<T> {
+    /// Create a handler which renders with the [DefaultRenderer] and the global colour choice.
+    pub fn new() -> Self {
+        Self::from(CustomErrors::new())
+    }
+
+    /// Use the given renderer to report the errors.
+    pub fn renderer(self, renderer: impl Renderer + Send + Sync + 'static) -> Self {
+        DiagnosticHandler {
+            renderer: Box::new(renderer),
+            ..self
+        }
+    }
+
+    /// Use the given colour choice to report the errors, instead of the global choice.
+    pub fn colour(self, choice: ColourChoice) -> Self {
+        DiagnosticHandler {
+            colour: Some(choice),
+            ..self
+        }
+    }
+
+    /// Add an error to this handler, this returns true if a limit of the collection is reached,
+    /// see [CustomErrors::push].
+    pub fn emit(&self, error: CustomError<T>) -> bool {
+        let mut emitted = self.emitted();
+        *emitted.counts.entry(error.level()).or_default() += 1;
+        emitted.errors.push(error)
+    }
+
+    /// The number of messages with the given severity emitted to this handler, including the
+    /// messages that were suppressed because a limit was reached.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let handler = DiagnosticHandler::from(CustomErrors::new().limit(1));
+    /// handler.emit(CustomError::new(ErrorType::NotANumber));
+    /// handler.emit(CustomError::new(ErrorType::NotANumber));
+    /// assert_eq!(handler.count(Severity::Error), 2);
+    /// assert_eq!(handler.take().iter().count(), 1);
+    /// ```
+    pub fn count(&self, severity: Severity) -> usize {
+        self.emitted()
+            .counts
+            .get(&severity)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Check if any errors (or more severe messages) were emitted to this handler, including
+    /// suppressed messages.
+    pub fn has_failed(&self) -> bool {
+        self.emitted()
+            .counts
+            .iter()
+            .any(|(severity, count)| *severity >= Severity::Error && *count > 0)
+    }
+
+    /// Take all errors emitted to this handler so far, leaving its collection empty (with the
+    /// same limits and policy) and the counts at zero.
+    pub fn take(&self) -> CustomErrors<T> {
+        let mut emitted = self.emitted();
+        emitted.counts.clear();
+        emitted.errors.take()
+    }
+
+    fn emitted(&self) -> MutexGuard<'_, Emitted<T>> {
+        // A panic while holding the lock cannot leave the collection in an invalid state
+        self.emitted.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<T: Send + 'static> DiagnosticHandler<T> {
+    /// Install this handler on the current thread while running the given function, all errors
+    /// emitted with [CustomError::emit] are added to this handler. Handlers can be nested, errors
+    /// are added to the innermost handler for their kind.
+    /// ```
+    /// use custom_error::*;
+    /// use std::sync::Arc;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let outer = Arc::new(DiagnosticHandler::<ErrorType>::new());
+    /// let inner = Arc::new(DiagnosticHandler::<ErrorType>::new());
+    /// outer.install(|| {
+    ///     inner.install(|| CustomError::new(ErrorType::NotANumber).emit());
+    ///     CustomError::new(ErrorType::NotANumber).warning().emit();
+    /// });
+    /// assert_eq!(inner.count(Severity::Error), 1);
+    /// assert_eq!(outer.count(Severity::Warning), 1);
+    /// assert!(!outer.has_failed());
+    ///
+    /// // Other threads can use the handler directly
+    /// let shared = outer.clone();
+    /// std::thread::spawn(move || shared.emit(CustomError::new(ErrorType::NotANumber)))
+    ///     .join()
+    ///     .unwrap();
+    /// assert!(outer.has_failed());
+    /// ```
+    pub fn install<R>(self: &Arc<Self>, f: impl FnOnce() -> R) -> R {
+        /// Remove the handler again, even if the function panics
+        struct Uninstall;
+        impl Drop for Uninstall {
+            fn drop(&mut self) {
+                HANDLERS.with(|handlers| handlers.borrow_mut().pop());
+            }
+        }
+
+        HANDLERS.with(|handlers| handlers.borrow_mut().push(self.clone()));
+        let _uninstall = Uninstall;
+        f()
+    }
+
+    /// Get the innermost handler for errors of this kind installed on this thread, if any.
+    fn installed() -> Option<Arc<Self>> {
+        HANDLERS.with(|handlers| {
+            handlers
+                .borrow()
+                .iter()
+                .rev()
+                .find_map(|handler| handler.clone().downcast::<Self>().ok())
+        })
+    }
+}
+
+impl<T: Debug> DiagnosticHandler<T> {
+    /// Render all errors emitted to this handler with the configured renderer and colour choice.
+    pub fn render(&self, out: &mut impl std::fmt::Write) -> std::fmt::Result {
+        scoped(self.colour, || {
+            self.emitted().errors.render(&*self.renderer, out)
+        })
+    }
+
+    /// Report all errors emitted to this handler on stderr, and return if any errors (or more
+    /// severe messages) were emitted.
+    pub fn report(&self) -> std::io::Result<bool> {
+        scoped_for(self.colour, &std::io::stderr(), || {
+            self.emitted()
+                .errors
+                .write_to(&*self.renderer, &mut std::io::stderr())
+        })?;
+        Ok(self.has_failed())
+    }
+}
+
+impl<T> Default for DiagnosticHandler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Use the given collection in the handler, to keep its errors, limits and policy.
+impl<T> From<CustomErrors<T>> for DiagnosticHandler<T> {
+    fn from(errors: CustomErrors<T>) -> Self {
+        let mut counts = BTreeMap::new();
+        for error in errors.iter() {
+            *counts.entry(error.level()).or_default() += 1;
+        }
+        DiagnosticHandler {
+            emitted: Mutex::new(Emitted { errors, counts }),
+            renderer: Box::new(DefaultRenderer::new()),
+            colour: None,
+        }
+    }
+}
+
+impl<T: Debug> Debug for DiagnosticHandler<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiagnosticHandler")
+            .field("errors", &self.emitted().errors)
+            .field("colour", &self.colour)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Debug + Send + 'static> CustomError<T> {
+    /// Emit this error to the innermost [DiagnosticHandler] for this kind of errors installed on
+    /// this thread. If there is no handler the error is printed to stderr directly. This returns
+    /// true if a limit of the handler is reached, see [CustomErrors::push].
+    pub fn emit(self) -> bool {
+        match DiagnosticHandler::installed() {
+            Some(handler) => handler.emit(self),
+            None => {
+                eprintln!("{}", self);
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::error::CustomError;
+use crate::errors::CustomErrors;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Serialization to JSON, only available with the `serde` feature. The kind is stored as
+/// it is serialized by serde, for C style enums this is the name of the variant. Every context
+/// is stored with its file, the linenumber of the first line (`line_start`), the lines and all
+/// highlights as spans from (`line`, `column`) to (`end_line`, `end_column`), where the lines
+/// are offsets in the lines of the context. The chain of causes is stored as a list of their
+/// messages (`causes`), so after deserialization only the messages of the causes are known.
+/// Suggestions are stored with their `span` of byte offsets, `replacement` and `applicability`.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// let error = CustomError::new(ErrorType::NotANumber)
+///     .message("The value provided was not a valid number")
+///     .context(Context::line("help l23").linenumber(10).file("numbers.txt").highlight((5, 3)))
+///     .source("l23".parse::<usize>().unwrap_err());
+/// let json = error.to_json().unwrap();
+/// assert!(json.contains(r#""kind":"NotANumber""#));
+/// assert!(json.contains(r#""causes":["invalid digit found in string"]"#));
+/// assert_eq!(CustomError::from_json(&json).unwrap(), error);
+/// ```
+impl<T: Serialize> CustomError<T> {
+    /// Serialize this error to JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+}
+
+/// Deserialization from JSON, only available with the `serde` feature.
+impl<T: DeserializeOwned> CustomError<T> {
+    /// Deserialize an error from JSON, as generated by [CustomError::to_json].
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Serialization to JSON, only available with the `serde` feature. The collection is
+/// stored as an object with the list of `errors`, each stored as described for [CustomError].
+/// Only serialization is needed to export errors, so the kind does not have to be deserializable.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug, serde::Serialize)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// let errors: CustomErrors<_> = std::iter::once(CustomError::new(ErrorType::NotANumber)).collect();
+/// assert!(errors.to_json().unwrap().contains(r#""kind":"NotANumber""#));
+/// ```
+impl<T: Serialize> CustomErrors<T> {
+    /// Serialize all errors to JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+}
+
+/// Deserialization from JSON, only available with the `serde` feature.
+impl<T: DeserializeOwned> CustomErrors<T> {
+    /// Deserialize a collection of errors from JSON, as generated by [CustomErrors::to_json].
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
@@ -39,11 +39,52 @@
 //!     }
 //! }
 //! ```
+mod ci;
+mod code;
+mod collect;
 mod colour;
 mod context;
 mod error;
 mod errors;
+mod explain;
+mod handler;
+#[cfg(feature = "serde")]
+mod json;
+mod lint;
+#[cfg(feature = "lsp")]
+mod lsp;
+mod policy;
+mod render;
+mod result;
+#[cfg(feature = "sarif")]
+mod sarif;
+mod source;
+mod std_error;
+mod suggestion;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+pub use ci::{GithubRenderer, GitlabRenderer};
+pub use code::{ErrorCode, ErrorKinds};
+pub use collect::{CollectErrors, ErrorSink};
+pub use colour::{ColourChoice, WithColour};
 pub use context::*;
+#[cfg(feature = "derive")]
+pub use custom_error_derive::ErrorKind;
 pub use error::*;
 pub use errors::CustomErrors;
+pub use explain::{Explanation, Registry};
+pub use handler::DiagnosticHandler;
+pub use lint::{LevelConfig, LevelConfigError, LintLevel};
+#[cfg(feature = "lsp")]
+pub use lsp::file_uri;
+#[cfg(feature = "lsp")]
+pub use lsp_types;
+pub use policy::RenderPolicy;
+pub use render::*;
+pub use result::CustomErrorContext;
+pub use source::Source;
+pub use std_error::{CustomErrorInputUnwrap, InputError, StdErrorKind};
+pub use suggestion::{
+    apply_suggestions, Applicability, Suggestion, SuggestionError, SuggestionView,
+};
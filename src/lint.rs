@@ -0,0 +1,248 @@
+use crate::context::Context;
+use crate::error::{CustomError, Severity};
+use crate::errors::CustomErrors;
+use crate::source::Source;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+/// The level set for a kind of error in a [LevelConfig], like the lint levels of clippy.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum LintLevel {
+    /// Leave the errors out
+    Allow,
+    /// Make the errors into warnings
+    Warn,
+    /// Make the errors into errors
+    Deny,
+    /// Make the errors into errors, this cannot be changed by later settings
+    Forbid,
+}
+
+impl LintLevel {
+    /// Get the lint level from its name, as used in config files
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(LintLevel::Allow),
+            "warn" => Some(LintLevel::Warn),
+            "deny" => Some(LintLevel::Deny),
+            "forbid" => Some(LintLevel::Forbid),
+            _ => None,
+        }
+    }
+}
+
+/// The errors that can occur when reading a [LevelConfig].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum LevelConfigError {
+    /// A line in a config file that is not a section header or a `key = level` pair
+    InvalidLine,
+    /// A level that is not one of `allow`, `warn`, `deny` or `forbid`
+    UnknownLevel,
+    /// A `-W`, `-D`, `-A` or `-F` argument without a code or kind after it
+    MissingCode,
+}
+
+/// The levels for kinds of errors, to promote, demote or leave out errors like the lint levels of
+/// clippy. The kinds are given by the code of an error (see [CustomError::coded]) or by the name
+/// of the kind (as displayed by [Debug]). Later settings override earlier settings, unless the
+/// earlier setting is [LintLevel::Forbid].
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+///     UnusedValue,
+///     LongLine,
+/// }
+/// let config = LevelConfig::new()
+///     .parse(&Source::new("lints.toml", "[lints]\nUnusedValue = \"deny\"\nLongLine = \"allow\"\n"))
+///     .unwrap()
+///     .args(["-W", "NotANumber"])
+///     .unwrap();
+/// let mut errors = CustomErrors::new();
+/// errors += CustomError::new(ErrorType::NotANumber);
+/// errors += CustomError::new(ErrorType::UnusedValue).warning();
+/// errors += CustomError::new(ErrorType::LongLine).warning();
+/// config.apply(&mut errors);
+/// let levels: Vec<_> = errors.iter().map(|e| e.level()).collect();
+/// assert_eq!(levels, vec![Severity::Warning, Severity::Error]);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default)]
+pub struct LevelConfig {
+    levels: BTreeMap<String, LintLevel>,
+}
+
+impl LevelConfig {
+    /// Create a new config which does not change any errors.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the level for the given code or kind.
+    pub fn set(mut self, kind: impl Into<String>, level: LintLevel) -> Self {
+        let kind = kind.into();
+        if self.levels.get(&kind) != Some(&LintLevel::Forbid) {
+            self.levels.insert(kind, level);
+        }
+        self
+    }
+
+    /// Get the level set for the given code or kind, if any.
+    pub fn get(&self, kind: &str) -> Option<LintLevel> {
+        self.levels.get(kind).copied()
+    }
+
+    /// Read the levels from a simple TOML or INI like config file. Every line is a `kind = level`
+    /// pair, quotes around the kind and the level are optional. Empty lines, comments (starting
+    /// with `#` or `;`) and section headers (like `[lints]`) are skipped. All invalid lines are
+    /// returned as errors.
+    /// ```
+    /// use custom_error::*;
+    /// let errors = LevelConfig::new()
+    ///     .parse(&Source::new("lints.toml", "NotANumber = \"deny\"\nUnusedValue = \"ignore\"\n"))
+    ///     .unwrap_err();
+    /// assert_eq!(errors.iter().next().unwrap().kind(), &LevelConfigError::UnknownLevel);
+    /// ```
+    pub fn parse(mut self, source: &Source) -> Result<Self, CustomErrors<LevelConfigError>> {
+        let mut errors = CustomErrors::new();
+        for (start, line) in source.lines() {
+            let content = line.trim();
+            if content.is_empty()
+                || content.starts_with(['#', ';'])
+                || (content.starts_with('[') && content.ends_with(']'))
+            {
+                continue;
+            }
+            let offset = start + (line.len() - line.trim_start().len());
+            let (kind, level) = match content.split_once('=') {
+                Some((kind, level)) if !unquote(kind).is_empty() => (kind, level),
+                _ => {
+                    errors += CustomError::new(LevelConfigError::InvalidLine)
+                        .title("Invalid line in level config")
+                        .context(Context::from_span(source, offset..offset + content.len()))
+                        .help("Use `kind = level`, with one of `allow`, `warn`, `deny` or `forbid` as level");
+                    continue;
+                }
+            };
+            match LintLevel::from_name(unquote(level)) {
+                Some(level) => self = self.set(unquote(kind), level),
+                None => {
+                    let level_start = offset + kind.len() + 1;
+                    let level_offset = level_start + (level.len() - level.trim_start().len());
+                    errors += CustomError::new(LevelConfigError::UnknownLevel)
+                        .title("Unknown level in level config")
+                        .context(Context::from_span(
+                            source,
+                            level_offset..level_start + level.trim_end().len(),
+                        ))
+                        .help("Use one of `allow`, `warn`, `deny` or `forbid`");
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(self)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Read the levels from command line arguments, like `-W code` to warn, `-D code` to deny,
+    /// `-A code` to allow and `-F code` to forbid. The code can also directly follow the flag, like
+    /// `-Dcode`. A code after a separate flag cannot start with `-`. All other arguments are
+    /// skipped, so all arguments of the program can be given.
+    /// ```
+    /// use custom_error::*;
+    /// let config = LevelConfig::new().args(["program", "-D", "E0001", "-AE0002"]).unwrap();
+    /// assert_eq!(config.get("E0001"), Some(LintLevel::Deny));
+    /// assert_eq!(config.get("E0002"), Some(LintLevel::Allow));
+    /// assert!(LevelConfig::new().args(["-W"]).is_err());
+    /// assert!(LevelConfig::new().args(["-W", "-D", "E0001"]).is_err());
+    /// ```
+    #[allow(clippy::result_large_err)] // Only called once at startup, so the size does not matter
+    pub fn args(
+        mut self,
+        args: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, CustomError<LevelConfigError>> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let arg = arg.as_ref();
+            let level = match arg.get(..2) {
+                Some("-A") => LintLevel::Allow,
+                Some("-W") => LintLevel::Warn,
+                Some("-D") => LintLevel::Deny,
+                Some("-F") => LintLevel::Forbid,
+                _ => continue,
+            };
+            let kind = match &arg[2..] {
+                "" => args
+                    .next()
+                    .map(|kind| kind.as_ref().to_string())
+                    // Another flag instead of a code
+                    .filter(|kind| !kind.starts_with('-'))
+                    .ok_or_else(|| {
+                        CustomError::new(LevelConfigError::MissingCode)
+                            .title("Missing code")
+                            .message(format!("Expected a code or kind after `{}`", arg))
+                    })?,
+                kind => kind.to_string(),
+            };
+            self = self.set(kind, level);
+        }
+        Ok(self)
+    }
+
+    /// Change the levels of the given errors according to this config, errors of an allowed kind
+    /// are left out. The code of an error is looked up before the name of its kind. Messages more
+    /// severe than errors (fatal errors and bugs) are never left out or lowered.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     Crashed,
+    ///     UnusedValue,
+    /// }
+    /// let mut fatal = CustomError::new(ErrorType::Crashed);
+    /// fatal.set_level(Severity::Fatal);
+    /// let mut errors = CustomErrors::new();
+    /// errors += fatal;
+    /// errors += CustomError::new(ErrorType::UnusedValue).warning();
+    /// let config = LevelConfig::new().set("Crashed", LintLevel::Deny).set("UnusedValue", LintLevel::Deny);
+    /// config.apply(&mut errors);
+    /// let levels: Vec<_> = errors.iter().map(|e| e.level()).collect();
+    /// assert_eq!(levels, vec![Severity::Fatal, Severity::Error]);
+    ///
+    /// LevelConfig::new().set("Crashed", LintLevel::Allow).apply(&mut errors);
+    /// assert_eq!(errors.iter().next().unwrap().level(), Severity::Fatal);
+    /// ```
+    pub fn apply<T: Debug>(&self, errors: &mut CustomErrors<T>) {
+        errors.retain(|error| {
+            error.level() > Severity::Error || self.level(error) != Some(LintLevel::Allow)
+        });
+        for error in errors.iter_mut() {
+            match self.level(error) {
+                _ if error.level() > Severity::Error => (),
+                Some(LintLevel::Warn) => error.set_level(Severity::Warning),
+                Some(LintLevel::Deny | LintLevel::Forbid) => {
+                    error.set_level(error.level().max(Severity::Error))
+                }
+                Some(LintLevel::Allow) | None => (),
+            }
+        }
+    }
+
+    /// Get the level set for the given error
+    fn level<T: Debug>(&self, error: &CustomError<T>) -> Option<LintLevel> {
+        error
+            .code()
+            .and_then(|code| self.get(code))
+            .or_else(|| self.get(&format!("{:?}", error.kind())))
+    }
+}
+
+/// Remove the whitespace and optional quotes around a key or value
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text)
+}
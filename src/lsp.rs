@@ -0,0 +1,183 @@
+use crate::error::{CustomError, Severity};
+use crate::errors::CustomErrors;
+use crate::render::{ContextView, HighlightView};
+use lsp_types::{
+    CodeDescription, Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location,
+    NumberOrString, Position, Range, Url,
+};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+impl From<Severity> for DiagnosticSeverity {
+    fn from(level: Severity) -> Self {
+        match level {
+            Severity::Error | Severity::Fatal | Severity::Bug => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+            Severity::Info | Severity::Note => DiagnosticSeverity::INFORMATION,
+            Severity::Help | Severity::Hint => DiagnosticSeverity::HINT,
+        }
+    }
+}
+
+/// Create a uri for the file of a context, this can be used as the default for
+/// [CustomError::to_lsp_diagnostic]. If the file is already a uri it is used as is, otherwise it
+/// is seen as a path, relative to the current directory if it is not absolute.
+pub fn file_uri(file: &str) -> Option<Url> {
+    match Url::parse(file) {
+        // A single letter scheme is a windows drive letter, not a uri
+        Ok(url) if url.scheme().len() > 1 => Some(url),
+        _ => {
+            let path = std::path::Path::new(file);
+            if path.is_absolute() {
+                Url::from_file_path(path).ok()
+            } else {
+                Url::from_file_path(std::env::current_dir().ok()?.join(path)).ok()
+            }
+        }
+    }
+}
+
+impl<T: Debug> CustomError<T> {
+    /// Convert this error into a Language Server Protocol diagnostic, together with the uri of the
+    /// file it belongs to. The first highlight (or the first line if there are no highlights) of
+    /// the first context with a file is the range of the diagnostic, all other highlights are
+    /// given as related information with their note as message. Messages added with
+    /// [CustomError::child] are given as related information as well if they have a context with
+    /// a file, otherwise they are added to the message. The linenumbers of the contexts
+    /// are seen as 1 based (as set by [Context::from_span](crate::Context::from_span)) and the
+    /// columns are converted to UTF-16 code units. Returns `None` if none of the contexts has a
+    /// file that can be converted to a uri.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let source = Source::new("/numbers.txt", "help 123\nhelp l23\n");
+    /// let error = CustomError::new(ErrorType::NotANumber)
+    ///     .context(Context::from_span(&source, 14..17));
+    /// let (uri, diagnostic) = error.to_lsp_diagnostic(file_uri).unwrap();
+    /// assert_eq!(uri.path(), "/numbers.txt");
+    /// assert_eq!(diagnostic.range.start, lsp_types::Position::new(1, 5));
+    /// assert_eq!(diagnostic.range.end, lsp_types::Position::new(1, 8));
+    /// ```
+    pub fn to_lsp_diagnostic(
+        &self,
+        uri: impl Fn(&str) -> Option<Url>,
+    ) -> Option<(Url, Diagnostic)> {
+        let view = self.view();
+        let mut locations = Vec::new();
+        for context in &view.contexts {
+            if let Some(uri) = context.file.and_then(&uri) {
+                if context.highlights.is_empty() {
+                    locations.push((uri, line_range(context), None));
+                } else {
+                    for highlight in &context.highlights {
+                        locations.push((uri.clone(), range(context, highlight), highlight.note));
+                    }
+                }
+            }
+        }
+        let mut locations = locations.into_iter();
+        let (file, primary, note) = locations.next()?;
+
+        let mut message: Vec<&str> = view.title.into_iter().chain(view.message).collect();
+        if message.is_empty() {
+            message.push(view.identifier());
+        }
+        let mut message = message.join("\n");
+        if let Some(note) = note {
+            message += &format!("\n{}", note);
+        }
+        if let Some(help) = view.help {
+            message += &format!("\nhelp: {}", help);
+        }
+        let mut related: Vec<_> = locations
+            .map(|(uri, range, note)| DiagnosticRelatedInformation {
+                location: Location::new(uri, range),
+                message: note.unwrap_or_default().to_string(),
+            })
+            .collect();
+        // Children with a file point to their own location, the others are part of the message
+        for child in &view.children {
+            match child
+                .context
+                .as_ref()
+                .and_then(|context| Some((context, context.file.and_then(&uri)?)))
+            {
+                Some((context, uri)) => related.push(DiagnosticRelatedInformation {
+                    location: Location::new(
+                        uri,
+                        context
+                            .highlights
+                            .first()
+                            .map_or_else(|| line_range(context), |h| range(context, h)),
+                    ),
+                    message: child.message.to_string(),
+                }),
+                None => message += &format!("\n{}: {}", child.level.name(), child.message),
+            }
+        }
+
+        Some((
+            file,
+            Diagnostic {
+                range: primary,
+                severity: Some(view.level.into()),
+                code: Some(NumberOrString::String(view.identifier().to_string())),
+                code_description: view
+                    .url
+                    .and_then(|url| Url::parse(url).ok())
+                    .map(|href| CodeDescription { href }),
+                message,
+                related_information: (!related.is_empty()).then_some(related),
+                ..Diagnostic::default()
+            },
+        ))
+    }
+}
+
+impl<T: Debug> CustomErrors<T> {
+    /// Convert all errors into Language Server Protocol diagnostics, grouped by the uri of their
+    /// file. See [CustomError::to_lsp_diagnostic], errors without a file are left out.
+    pub fn to_lsp_diagnostics(
+        &self,
+        uri: impl Fn(&str) -> Option<Url>,
+    ) -> BTreeMap<Url, Vec<Diagnostic>> {
+        let mut output: BTreeMap<Url, Vec<Diagnostic>> = BTreeMap::new();
+        for (file, diagnostic) in self.iter().filter_map(|e| e.to_lsp_diagnostic(&uri)) {
+            output.entry(file).or_default().push(diagnostic);
+        }
+        output
+    }
+}
+
+/// The range of a highlight
+fn range(context: &ContextView<'_>, highlight: &HighlightView<'_>) -> Range {
+    let (end_line, end_column) = highlight.end_position();
+    Range::new(
+        position(context, highlight.line, highlight.column),
+        position(context, end_line, end_column),
+    )
+}
+
+/// The range of the first line of a context
+fn line_range(context: &ContextView<'_>) -> Range {
+    let length = context
+        .lines
+        .first()
+        .map_or(0, |line| line.encode_utf16().count());
+    let start = position(context, 0, 0);
+    Range::new(start, Position::new(start.line, length as u32))
+}
+
+/// The position of the given column (in the unit of the context) on the given line, with the
+/// character offset counted in UTF-16 code units
+fn position(context: &ContextView<'_>, line: usize, column: usize) -> Position {
+    let character = context.utf16_column(line, column);
+    // Linenumbers are 1 based, LSP lines are 0 based
+    let line = context
+        .linenumber
+        .map_or(line, |n| n.saturating_sub(1) + line);
+    Position::new(line as u32, character as u32)
+}
@@ -0,0 +1,128 @@
+use crate::render::{ErrorView, Renderer};
+use std::collections::{HashMap, HashSet};
+
+/// How a collection of errors is reduced before it is rendered, to keep the output readable if
+/// the same problem is found many times. By default all errors are rendered in the order they
+/// were added. See [CustomErrors::policy](crate::CustomErrors::policy). The policy is only applied
+/// by renderers for humans, like the [DefaultRenderer](crate::DefaultRenderer), machine readable
+/// output always contains all errors (see [Renderer::apply_policy](crate::Renderer::apply_policy)).
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// let source = Source::new("numbers.txt", "l23\nl23\nl23\n");
+/// let mut errors = CustomErrors::new()
+///     .policy(RenderPolicy::new().deduplicate().collapse(1));
+/// for line in 0..3 {
+///     errors += CustomError::new(ErrorType::NotANumber).context(Context::from_span(&source, line * 4..line * 4 + 1));
+///     // Added twice by mistake
+///     errors += CustomError::new(ErrorType::NotANumber).context(Context::from_span(&source, line * 4..line * 4 + 1));
+/// }
+/// let output = errors.with_colour(ColourChoice::Never).to_string();
+/// assert_eq!(output.matches("╭──").count(), 1);
+/// assert!(output.contains("... and 2 more ") && output.contains("ErrorType::NotANumber errors"));
+/// assert!(output.ends_with("encountered: 3 errors"));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct RenderPolicy {
+    deduplicate: bool,
+    collapse: Option<usize>,
+    group_by_file: bool,
+}
+
+impl RenderPolicy {
+    /// Create a new policy which renders all errors in the order they were added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only render the first of identical errors, errors are identical if they render to the same
+    /// output. Duplicates are not counted in the summary.
+    pub fn deduplicate(self) -> Self {
+        RenderPolicy {
+            deduplicate: true,
+            ..self
+        }
+    }
+
+    /// Only render the first `max` errors of every kind (or code) and level, the number of other
+    /// errors of that kind is noted after the last rendered one.
+    pub fn collapse(self, max: usize) -> Self {
+        RenderPolicy {
+            collapse: Some(max),
+            ..self
+        }
+    }
+
+    /// Render the errors ordered by the file of their first context, errors without a file are
+    /// rendered last. Errors for the same file stay in the order they were added.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let a = Source::new("a.txt", "l23\n");
+    /// let b = Source::new("b.txt", "l23\n");
+    /// let mut errors = CustomErrors::new().policy(RenderPolicy::new().group_by_file());
+    /// errors += CustomError::new(ErrorType::NotANumber).context(Context::from_span(&b, 0..1));
+    /// errors += CustomError::new(ErrorType::NotANumber).context(Context::from_span(&a, 0..1));
+    /// let output = errors.with_colour(ColourChoice::Never).to_string();
+    /// assert!(output.find("a.txt").unwrap() < output.find("b.txt").unwrap());
+    /// ```
+    pub fn group_by_file(self) -> Self {
+        RenderPolicy {
+            group_by_file: true,
+            ..self
+        }
+    }
+
+    /// Apply this policy to the given errors, as rendered by the given renderer.
+    pub(crate) fn apply<'a>(
+        &self,
+        mut views: Vec<ErrorView<'a>>,
+        renderer: &dyn Renderer,
+    ) -> Vec<ErrorView<'a>> {
+        if self.deduplicate {
+            let mut seen = HashSet::new();
+            views.retain(|view| {
+                let mut output = String::new();
+                // An error that cannot be rendered is never the same as another error
+                renderer.render_error(view, &mut output).is_err() || seen.insert(output)
+            });
+        }
+        if self.group_by_file {
+            // A stable sort, so errors for the same file stay in order
+            views.sort_by_key(|view| {
+                let file = view.contexts.iter().find_map(|context| context.file);
+                (file.is_none(), file)
+            });
+        }
+        if let Some(max) = self.collapse {
+            // At least one error is rendered to note the collapsed errors with
+            let max = max.max(1);
+            // The number of rendered errors of every kind, and the index of the last one
+            let mut kinds: HashMap<_, (usize, usize)> = HashMap::new();
+            let mut output: Vec<ErrorView<'a>> = Vec::new();
+            for view in views {
+                let key = (view.level, view.identifier().to_string());
+                match kinds.get_mut(&key) {
+                    Some((count, last)) if *count >= max => output[*last].collapsed += 1,
+                    Some((count, last)) => {
+                        *count += 1;
+                        *last = output.len();
+                        output.push(view);
+                    }
+                    None => {
+                        kinds.insert(key, (1, output.len()));
+                        output.push(view);
+                    }
+                }
+            }
+            views = output;
+        }
+        views
+    }
+}
@@ -0,0 +1,718 @@
+use crate::colour::*;
+use crate::context::OffsetUnit;
+use crate::error::Severity;
+use crate::suggestion::SuggestionView;
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt::Write;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A way of rendering errors, for example to implement your own house style. The default look is
+/// implemented by [DefaultRenderer]. The renderer gets a structured view of the error and can
+/// write its output to anything implementing [std::fmt::Write].
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+///
+/// /// Render every error on a single line
+/// struct Short;
+///
+/// impl Renderer for Short {
+///     fn render_error(&self, error: &ErrorView<'_>, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+///         write!(out, "{}: {}", error.level, error.kind)?;
+///         if let Some(message) = error.message {
+///             write!(out, ": {}", message)?;
+///         }
+///         writeln!(out)
+///     }
+/// }
+///
+/// let error = CustomError::new(ErrorType::NotANumber).message("Expected a number");
+/// let mut output = String::new();
+/// error.render(&Short, &mut output).unwrap();
+/// assert!(output.ends_with("ErrorType::NotANumber: Expected a number\n"));
+/// ```
+pub trait Renderer {
+    /// Render a single error
+    fn render_error(&self, error: &ErrorView<'_>, out: &mut dyn Write) -> std::fmt::Result;
+
+    /// If the [RenderPolicy](crate::RenderPolicy) of a collection is applied before rendering it
+    /// with this renderer. This is only meant for output read by humans, machine readable formats
+    /// should get every error. By default the policy is not applied.
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let source = Source::new("numbers.txt", "l23\nl23\nl23\n");
+    /// let mut errors = CustomErrors::new().policy(RenderPolicy::new().collapse(1));
+    /// for line in 0..3 {
+    ///     errors += CustomError::new(ErrorType::NotANumber).context(Context::from_span(&source, line * 4..line * 4 + 1));
+    /// }
+    /// let mut output = String::new();
+    /// errors.render(&GithubRenderer, &mut output).unwrap();
+    /// assert_eq!(output.lines().count(), 3);
+    /// ```
+    fn apply_policy(&self) -> bool {
+        false
+    }
+
+    /// Render a collection of errors, by default this renders every error followed by an empty
+    /// line and ends with the summary.
+    fn render_errors(
+        &self,
+        errors: &[ErrorView<'_>],
+        summary: &Summary,
+        out: &mut dyn Write,
+    ) -> std::fmt::Result {
+        for error in errors {
+            self.render_error(error, out)?;
+            writeln!(out)?;
+        }
+        self.render_summary(summary, out)
+    }
+
+    /// Render the summary of a collection of errors, with the number of messages of every level.
+    fn render_summary(&self, summary: &Summary, out: &mut dyn Write) -> std::fmt::Result {
+        if summary.total() + summary.suppressed == 0 {
+            writeln!(out, "\n{}", green("no messages!"))?;
+        } else {
+            write!(out, "\nencountered: ")?;
+            // The most severe messages first
+            let counts: Vec<String> = Severity::all()
+                .into_iter()
+                .rev()
+                .filter(|severity| summary.count(*severity) > 0)
+                .map(|severity| {
+                    format!(
+                        "{} {}",
+                        summary.count(severity),
+                        severity.in_colour(severity.plural())
+                    )
+                })
+                .collect();
+            write!(out, "{}", counts.join(", "))?;
+            if summary.suppressed > 0 {
+                write!(
+                    out,
+                    " ({} more suppressed after reaching the limit)",
+                    summary.suppressed
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A structured view of a [CustomError](struct@crate::CustomError), as given to a [Renderer].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct ErrorView<'a> {
+    /// The level of the error
+    pub level: Severity,
+    /// The identifier of the kind of the error, the path of the type followed by the kind
+    pub kind: String,
+    /// The code of the error, if created with [CustomError::coded](crate::CustomError::coded)
+    pub code: Option<&'a str>,
+    /// The title, if given
+    pub title: Option<&'a str>,
+    /// The message, if given
+    pub message: Option<&'a str>,
+    /// The help message, if given
+    pub help: Option<&'a str>,
+    /// The url to extra documentation, if given
+    pub url: Option<&'a str>,
+    /// The location in the source code where the error was generated, if known
+    pub location: Option<&'a str>,
+    /// All contexts of the error
+    pub contexts: Vec<ContextView<'a>>,
+    /// All suggestions to fix the error
+    pub suggestions: Vec<SuggestionView<'a>>,
+    /// The number of errors of the same kind and level that are not rendered, and should be noted
+    /// after this error, see [RenderPolicy::collapse](crate::RenderPolicy::collapse)
+    pub collapsed: usize,
+    /// All messages to display beneath the error, in order
+    pub children: Vec<ChildView<'a>>,
+    /// The chain of errors that caused this error, the direct cause first. Render them with
+    /// [render_cause] to not repeat the chain for causes that are [CustomError](struct@crate::CustomError)s.
+    pub causes: Vec<&'a (dyn Error + 'static)>,
+}
+
+thread_local! {
+    /// If a cause is being rendered on this thread
+    static RENDERING_CAUSE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// If a cause is being rendered on this thread, in which case the chain of causes should not be
+/// rendered again
+pub(crate) fn rendering_cause() -> bool {
+    RENDERING_CAUSE.with(|flag| flag.get())
+}
+
+/// Iterate over the given error and all errors in its chain of sources
+pub(crate) fn cause_chain<'a>(
+    first: Option<&'a (dyn Error + 'static)>,
+) -> impl Iterator<Item = &'a (dyn Error + 'static)> {
+    fn next<'a>(cause: &&'a (dyn Error + 'static)) -> Option<&'a (dyn Error + 'static)> {
+        (*cause).source()
+    }
+    std::iter::successors(first, next)
+}
+
+/// Render the message of a cause of an error. If the cause is a [CustomError](struct@crate::CustomError)
+/// its own causes are left out, as they are part of the chain of the outer error already.
+pub fn render_cause(cause: &(dyn Error + 'static)) -> String {
+    let previous = RENDERING_CAUSE.with(|flag| flag.replace(true));
+    let output = cause.to_string();
+    RENDERING_CAUSE.with(|flag| flag.set(previous));
+    output
+}
+
+/// A structured view of a message displayed beneath an error, see
+/// [CustomError::child](crate::CustomError::child).
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct ChildView<'a> {
+    /// The level of the message
+    pub level: Severity,
+    /// The message
+    pub message: &'a str,
+    /// The context of the message, if given
+    pub context: Option<ContextView<'a>>,
+}
+
+/// A structured view of a [Context](crate::Context), as given to a [Renderer].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct ContextView<'a> {
+    /// The lines of the context
+    pub lines: &'a [String],
+    /// The linenumber of the first line, if given
+    pub linenumber: Option<usize>,
+    /// The file name, if given
+    pub file: Option<&'a str>,
+    /// All highlights on the lines
+    pub highlights: Vec<HighlightView<'a>>,
+    /// The unit in which the columns and lengths of the highlights are given
+    pub unit: OffsetUnit,
+    /// The number of columns a tab is expanded to
+    pub tab_width: usize,
+}
+
+/// A structured view of a [Highlight](crate::Highlight), as given to a [Renderer].
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct HighlightView<'a> {
+    /// The line offset in the list of lines for a context
+    pub line: usize,
+    /// The column in the specified line
+    pub column: usize,
+    /// The length of the highlight, only used for highlights on a single line
+    pub length: usize,
+    /// The line offset and column where the highlight ends (exclusive), only given for
+    /// highlights spanning multiple lines
+    pub end: Option<(usize, usize)>,
+    /// The note to display after the highlight, if given
+    pub note: Option<&'a str>,
+    /// The level of the highlight
+    pub level: Severity,
+}
+
+/// The number of messages of every level in a collection of errors.
+#[non_exhaustive]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct Summary {
+    /// The number of bugs
+    pub bugs: usize,
+    /// The number of fatal errors
+    pub fatals: usize,
+    /// The number of errors
+    pub errors: usize,
+    /// The number of warnings
+    pub warnings: usize,
+    /// The number of informational messages
+    pub infos: usize,
+    /// The number of notes
+    pub notes: usize,
+    /// The number of help messages
+    pub helps: usize,
+    /// The number of hints
+    pub hints: usize,
+    /// The number of messages that were suppressed because a limit was reached, see
+    /// [CustomErrors::limit](crate::CustomErrors::limit)
+    pub suppressed: usize,
+}
+
+impl Summary {
+    /// Count the messages of every level in the given errors
+    pub fn new(errors: &[ErrorView<'_>]) -> Self {
+        let mut summary = Summary::default();
+        for error in errors {
+            // Collapsed errors are counted as well
+            let count = 1 + error.collapsed;
+            *summary.count_mut(error.level) += count;
+        }
+        summary
+    }
+
+    /// The number of messages with the given severity
+    pub fn count(&self, severity: Severity) -> usize {
+        let mut summary = *self;
+        *summary.count_mut(severity)
+    }
+
+    /// The number of messages of all severities, without the suppressed messages
+    pub fn total(&self) -> usize {
+        Severity::all().into_iter().map(|s| self.count(s)).sum()
+    }
+
+    fn count_mut(&mut self, severity: Severity) -> &mut usize {
+        match severity {
+            Severity::Hint => &mut self.hints,
+            Severity::Help => &mut self.helps,
+            Severity::Note => &mut self.notes,
+            Severity::Info => &mut self.infos,
+            Severity::Warning => &mut self.warnings,
+            Severity::Error => &mut self.errors,
+            Severity::Fatal => &mut self.fatals,
+            Severity::Bug => &mut self.bugs,
+        }
+    }
+}
+
+/// The renderer used when displaying errors, with a header, boxed contexts, messages and help.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// let error = CustomError::new(ErrorType::NotANumber);
+/// let mut output = String::new();
+/// error.render(&DefaultRenderer::new().colour(ColourChoice::Never), &mut output).unwrap();
+/// assert_eq!(output, error.with_colour(ColourChoice::Never).to_string());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+pub struct DefaultRenderer {
+    colour: Option<ColourChoice>,
+}
+
+impl DefaultRenderer {
+    /// Create a new default renderer, which uses the global colour choice.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use the given colour choice instead of the global colour choice.
+    pub fn colour(self, choice: ColourChoice) -> Self {
+        Self {
+            colour: Some(choice),
+        }
+    }
+
+    /// Render a single suggestion, with the changed lines before (`-`) and after (`+`) the change.
+    pub fn render_suggestion(
+        &self,
+        suggestion: &SuggestionView<'_>,
+        out: &mut dyn Write,
+    ) -> std::fmt::Result {
+        scoped(self.colour, || {
+            let last = suggestion.linenumber
+                + suggestion
+                    .removed
+                    .len()
+                    .max(suggestion.added.len())
+                    .saturating_sub(1);
+            let linenumber_padding = last.to_string().len();
+            match suggestion.message {
+                Some(message) => writeln!(out, "  {}: {}", green("suggestion"), message)?,
+                None => writeln!(out, "  {}", green("suggestion"))?,
+            }
+            writeln!(out, "{:pad$} {}", "", blue("╷"), pad = linenumber_padding)?;
+            for (lines, sign) in [
+                (suggestion.removed, red("-")),
+                (suggestion.added, green("+")),
+            ] {
+                for (index, line) in lines.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "{} {} {}",
+                        grey(format!(
+                            "{:>pad$}",
+                            suggestion.linenumber + index,
+                            pad = linenumber_padding
+                        )),
+                        sign,
+                        line
+                    )?;
+                }
+            }
+            writeln!(out, "{:pad$} {}", "", blue("╵"), pad = linenumber_padding)
+        })
+    }
+
+    /// Render a single context, with its lines and highlights.
+    pub fn render_context(
+        &self,
+        context: &ContextView<'_>,
+        out: &mut dyn Write,
+    ) -> std::fmt::Result {
+        scoped(self.colour, || {
+            // Determine how many chars are needed to display the biggest line number, default is 1 to have at least 1 character
+            let linenumber_padding = ((context.linenumber.unwrap_or(1) + context.lines.len())
+                as f64)
+                .log10()
+                .ceil() as usize;
+
+            if let Some(file) = &context.file {
+                if context.highlights.len() == 1 {
+                    // Show the filename and location of the highlight (if there is only one)
+                    writeln!(
+                        out,
+                        "{:pad$} {}[{}{}]",
+                        "",
+                        blue("╭──"),
+                        file,
+                        context
+                            .linenumber // Show the linenumber followed by the column if the linenumber is known
+                            .map(|l| {
+                                let highlight = &context.highlights[0];
+                                format!(
+                                    ":{}:{}",
+                                    l + highlight.line,
+                                    context.char_column(highlight.line, highlight.column)
+                                )
+                            })
+                            .unwrap_or_default(),
+                        pad = linenumber_padding
+                    )?;
+                } else {
+                    // If there are no or multiple highlights only show the filename
+                    writeln!(
+                        out,
+                        "{:pad$} {}[{}]",
+                        "",
+                        blue("╭──"),
+                        file,
+                        pad = linenumber_padding
+                    )?;
+                }
+                // Extend the sideline so that it provides a single line of border between the file header and content
+                writeln!(out, "{:pad$} {}", "", blue("│"), pad = linenumber_padding)?;
+            } else {
+                // If there is no file known just end the sideline nicely
+                writeln!(out, "{:pad$} {}", "", blue("╷"), pad = linenumber_padding)?;
+            }
+
+            // Multi line highlights are drawn as brackets in a margin before the lines, every
+            // multi line highlight gets its own column in this margin
+            let multiline: Vec<&HighlightView> = context
+                .highlights
+                .iter()
+                .filter(|h| h.end.is_some())
+                .collect();
+            let mut open = vec![false; multiline.len()];
+            // Build the margin, with the bracket corner for the given highlight (if any)
+            let margin = |open: &[bool], corner: Option<(usize, &str)>| {
+                let mut output = String::new();
+                for (index, highlight) in multiline.iter().enumerate() {
+                    match corner {
+                        Some((c, symbol)) if c == index => {
+                            output += &highlight.level.in_colour(symbol)
+                        }
+                        Some((c, _)) if c < index => output += &multiline[c].level.in_colour("──"),
+                        _ if open[index] => output += &highlight.level.in_colour("│ "),
+                        _ => output += "  ",
+                    }
+                }
+                output
+            };
+
+            // Use offset numbers if there is no linenumber given
+            let linenumber = context.linenumber.unwrap_or(0);
+            for (index, line) in context.lines.iter().enumerate() {
+                // Write the current line
+                writeln!(
+                    out,
+                    "{} {} {}{}",
+                    // Pad before colouring, otherwise the escape codes are counted for the padding
+                    grey(format!(
+                        "{:>pad$}",
+                        linenumber + index,
+                        pad = linenumber_padding
+                    )),
+                    blue("│"),
+                    margin(&open, None),
+                    line.replace('\t', &" ".repeat(context.tab_width)),
+                )?;
+                // Start the brackets for multi line highlights starting on this line
+                for (number, highlight) in multiline.iter().enumerate() {
+                    if index == highlight.line {
+                        writeln!(
+                            out,
+                            "{:>pad$} {} {}{}",
+                            "",
+                            blue("·"),
+                            margin(&open, Some((number, "╭─"))),
+                            highlight.level.in_colour(
+                                "─".repeat(
+                                    context.display_range(highlight.line, highlight.column, 0).0
+                                ) + "╯"
+                            ),
+                            pad = linenumber_padding,
+                        )?;
+                        open[number] = true;
+                    }
+                }
+                // Determine if there needs to be a highlight
+                for highlight in &context.highlights {
+                    if index == highlight.line && highlight.end.is_none() {
+                        let (column, width) = context.display_range(
+                            highlight.line,
+                            highlight.column,
+                            highlight.length,
+                        );
+                        writeln!(
+                            out,
+                            "{:>pad$} {} {}{}{}{}",
+                            "",
+                            blue("·"),
+                            margin(&open, None),
+                            " ".repeat(column),
+                            highlight.level.in_colour("─".repeat(width)),
+                            highlight.level.in_colour(
+                                highlight
+                                    .note
+                                    .map(|n| " ".to_string() + n)
+                                    .unwrap_or_default()
+                            ),
+                            pad = linenumber_padding,
+                        )?;
+                    }
+                }
+                // End the brackets for multi line highlights ending on this line, with the note at the end
+                for (number, highlight) in multiline.iter().enumerate() {
+                    if let Some((end_line, end_column)) = highlight.end {
+                        if index == end_line {
+                            // Point to the last character in the highlight
+                            let column = context
+                                .display_range(end_line, end_column.saturating_sub(1), 0)
+                                .0;
+                            writeln!(
+                                out,
+                                "{:>pad$} {} {}{}{}",
+                                "",
+                                blue("·"),
+                                margin(&open, Some((number, "╰─"))),
+                                highlight.level.in_colour("─".repeat(column) + "╯"),
+                                highlight.level.in_colour(
+                                    highlight
+                                        .note
+                                        .as_ref()
+                                        .map(|n| " ".to_string() + n)
+                                        .unwrap_or_default()
+                                ),
+                                pad = linenumber_padding,
+                            )?;
+                            open[number] = false;
+                        }
+                    }
+                }
+            }
+            // Nicely end the sideline
+            writeln!(out, "{:pad$} {}", "", blue("╵"), pad = linenumber_padding)?;
+            Ok(())
+        })
+    }
+}
+
+impl Renderer for DefaultRenderer {
+    fn apply_policy(&self) -> bool {
+        true
+    }
+
+    fn render_error(&self, error: &ErrorView<'_>, out: &mut dyn Write) -> std::fmt::Result {
+        scoped(self.colour, || {
+            if let Some(code) = error.code {
+                write!(
+                    out,
+                    "{}{}",
+                    error.level,
+                    error.level.in_colour(format!("[{}]", code))
+                )?;
+                match error.title {
+                    Some(title) => writeln!(out, ": {}", title)?,
+                    None => writeln!(out)?,
+                }
+            } else if let Some(title) = error.title {
+                writeln!(out, "{}: {} ({})", error.level, title, error.kind)?;
+            } else {
+                writeln!(out, "{}: {}", error.level, error.kind)?;
+            }
+            if let Some(url) = error.url {
+                writeln!(out, "{}: {}", blue("url"), blue(url))?;
+            } //┅┅┅┅ ┉┉┉┉┉┉ ┗━━━━━━┛ ╍╍╍╍╍╍ ══════════ ╰────╯╭
+            if let Some(location) = error.location {
+                writeln!(out, "  {} generated at: {}", blue("-->"), location)?;
+            }
+            for context in &error.contexts {
+                self.render_context(context, out)?;
+            }
+            for suggestion in &error.suggestions {
+                self.render_suggestion(suggestion, out)?;
+            }
+            if let Some(message) = error.message {
+                writeln!(out, "{}", message)?;
+            }
+            if let Some(help) = error.help {
+                writeln!(out, "  {}: {}", blue("help"), help)?;
+            }
+            for child in &error.children {
+                writeln!(out, "  {}: {}", child.level, child.message)?;
+                if let Some(context) = &child.context {
+                    self.render_context(context, out)?;
+                }
+            }
+            for cause in &error.causes {
+                let cause = render_cause(*cause);
+                // Indent the following lines of causes spanning multiple lines, like custom errors
+                writeln!(
+                    out,
+                    "  {}: {}",
+                    blue("caused by"),
+                    cause.trim_end().replace('\n', "\n    ")
+                )?;
+            }
+            if error.collapsed > 0 {
+                writeln!(
+                    out,
+                    "... and {} more {} {}",
+                    error.collapsed,
+                    error.identifier(),
+                    error.level.in_colour(error.level.plural())
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    fn render_errors(
+        &self,
+        errors: &[ErrorView<'_>],
+        summary: &Summary,
+        out: &mut dyn Write,
+    ) -> std::fmt::Result {
+        scoped(self.colour, || {
+            for error in errors {
+                self.render_error(error, out)?;
+                writeln!(out)?;
+            }
+            self.render_summary(summary, out)
+        })
+    }
+}
+
+impl ContextView<'_> {
+    /// Get the byte index in the line for the given offset (in the unit of this context). If the
+    /// offset is past the end of the line the number of units beyond the end is returned as well.
+    fn byte_index(&self, line: &str, offset: usize) -> (usize, usize) {
+        let end = std::iter::once(line.len());
+        let index = match self.unit {
+            // Round down to a char boundary, to never slice in the middle of a char
+            OffsetUnit::Byte => (offset <= line.len()).then(|| {
+                (0..=offset)
+                    .rev()
+                    .find(|i| line.is_char_boundary(*i))
+                    .unwrap()
+            }),
+            OffsetUnit::Char => line.char_indices().map(|(i, _)| i).chain(end).nth(offset),
+            OffsetUnit::Grapheme => line
+                .grapheme_indices(true)
+                .map(|(i, _)| i)
+                .chain(end)
+                .nth(offset),
+        };
+        index.map_or_else(
+            || {
+                let units = match self.unit {
+                    OffsetUnit::Byte => line.len(),
+                    OffsetUnit::Char => line.chars().count(),
+                    OffsetUnit::Grapheme => line.graphemes(true).count(),
+                };
+                (line.len(), offset - units)
+            },
+            |index| (index, 0),
+        )
+    }
+
+    /// The display width of the given text, with tabs expanded
+    pub fn display_width(&self, text: &str) -> usize {
+        text.graphemes(true)
+            .map(|g| if g == "\t" { self.tab_width } else { g.width() })
+            .sum()
+    }
+
+    /// Get the display column and display width for the given range (in the unit of this context)
+    /// on the given line, with tabs expanded. Columns past the end of the line count as a single
+    /// column each.
+    pub fn display_range(&self, line: usize, column: usize, length: usize) -> (usize, usize) {
+        let text = self.lines.get(line).map_or("", |l| l.as_str());
+        let (start, start_overflow) = self.byte_index(text, column);
+        let (end, end_overflow) = self.byte_index(text, column + length);
+        let display_column = self.display_width(&text[..start]) + start_overflow;
+        let width = self.display_width(&text[start..end]) + end_overflow - start_overflow;
+        // Always show something if the highlight is not empty, even if it is just a combining mark
+        (display_column, if length > 0 { width.max(1) } else { 0 })
+    }
+
+    /// Get the column in UTF-16 code units for the given column (in the unit of this context) on
+    /// the given line, as used by editors. Columns past the end of the line count as a single
+    /// code unit each.
+    pub fn utf16_column(&self, line: usize, column: usize) -> usize {
+        let text = self.lines.get(line).map_or("", |l| l.as_str());
+        let (index, overflow) = self.byte_index(text, column);
+        text[..index].encode_utf16().count() + overflow
+    }
+
+    /// Get the column in chars for the given column (in the unit of this context) on the given
+    /// line. Columns past the end of the line count as a single char each.
+    pub fn char_column(&self, line: usize, column: usize) -> usize {
+        let text = self.lines.get(line).map_or("", |l| l.as_str());
+        let (index, overflow) = self.byte_index(text, column);
+        text[..index].chars().count() + overflow
+    }
+}
+
+impl ErrorView<'_> {
+    /// The code of the error if given, otherwise the kind
+    pub fn identifier(&self) -> &str {
+        self.code.unwrap_or(&self.kind)
+    }
+
+    /// The title and message (or the kind if neither is given) followed by the help, as plain text
+    /// for tools that show the message without the contexts
+    pub(crate) fn plain_message(&self) -> String {
+        let mut message: Vec<&str> = self.title.into_iter().chain(self.message).collect();
+        if message.is_empty() {
+            message.push(self.identifier());
+        }
+        let mut message = message.join("\n");
+        if let Some(help) = self.help {
+            message += &format!("\nhelp: {}", help);
+        }
+        message
+    }
+}
+
+impl HighlightView<'_> {
+    /// The line offset and column where this highlight ends (exclusive), for both single and
+    /// multi line highlights
+    pub fn end_position(&self) -> (usize, usize) {
+        self.end.unwrap_or((self.line, self.column + self.length))
+    }
+}
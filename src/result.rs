@@ -0,0 +1,111 @@
+use crate::context::Context;
+use crate::error::CustomError;
+use crate::errors::CustomErrors;
+
+/// A trait to enrich errors as they travel up the stack, for example to add the file name that
+/// is only known in an outer function. It is implemented for results with a [CustomError] or
+/// [CustomErrors] as error, for [CustomErrors] every error is changed.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+///
+/// fn parse_line(line: &str) -> Result<usize, CustomError<ErrorType>> {
+///     line.parse().map_err(|_| {
+///         CustomError::new(ErrorType::NotANumber).context(Context::line(line).highlight((0, line.len())))
+///     })
+/// }
+///
+/// fn parse_file(name: &str, text: &str) -> Result<Vec<usize>, CustomError<ErrorType>> {
+///     text.lines()
+///         .enumerate()
+///         .map(|(index, line)| {
+///             parse_line(line)
+///                 .with_file(name)
+///                 .with_context(|| Context::line(format!("line {}", index + 1)))
+///                 .with_help("Only use the digits 0-9")
+///         })
+///         .collect()
+/// }
+///
+/// let error = parse_file("numbers.txt", "123\nl23\n").unwrap_err();
+/// let output = error.with_colour(ColourChoice::Never).to_string();
+/// assert!(output.contains("numbers.txt"));
+/// assert!(output.contains("help: Only use the digits 0-9"));
+/// ```
+pub trait CustomErrorContext: Sized {
+    /// Add the context given by the function to the error, the function is only called if there
+    /// is an error.
+    fn with_context(self, context: impl FnOnce() -> Context) -> Self;
+
+    /// Set the file of all contexts of the error that do not have a file yet, see [CustomError::file].
+    fn with_file(self, file: impl Into<String>) -> Self;
+
+    /// Set the help message of the error.
+    fn with_help(self, help: impl Into<String>) -> Self;
+
+    /// Set the message of the error.
+    fn with_message(self, message: impl Into<String>) -> Self;
+
+    /// Make the error into a warning.
+    #[allow(clippy::wrong_self_convention)]
+    fn as_warning(self) -> Self;
+}
+
+impl<T, E> CustomErrorContext for Result<T, CustomError<E>> {
+    fn with_context(self, context: impl FnOnce() -> Context) -> Self {
+        self.map_err(|e| e.context(context()))
+    }
+
+    fn with_file(self, file: impl Into<String>) -> Self {
+        self.map_err(|e| e.file(file))
+    }
+
+    fn with_help(self, help: impl Into<String>) -> Self {
+        self.map_err(|e| e.help(help))
+    }
+
+    fn with_message(self, message: impl Into<String>) -> Self {
+        self.map_err(|e| e.message(message))
+    }
+
+    fn as_warning(self) -> Self {
+        self.map_err(CustomError::warning)
+    }
+}
+
+impl<T, E> CustomErrorContext for Result<T, CustomErrors<E>> {
+    fn with_context(self, context: impl FnOnce() -> Context) -> Self {
+        self.map_err(|errors| {
+            let context = context();
+            errors.map(|e| e.context(context.clone()))
+        })
+    }
+
+    fn with_file(self, file: impl Into<String>) -> Self {
+        self.map_err(|errors| {
+            let file = file.into();
+            errors.map(|e| e.file(file.clone()))
+        })
+    }
+
+    fn with_help(self, help: impl Into<String>) -> Self {
+        self.map_err(|errors| {
+            let help = help.into();
+            errors.map(|e| e.help(help.clone()))
+        })
+    }
+
+    fn with_message(self, message: impl Into<String>) -> Self {
+        self.map_err(|errors| {
+            let message = message.into();
+            errors.map(|e| e.message(message.clone()))
+        })
+    }
+
+    fn as_warning(self) -> Self {
+        self.map_err(|errors| errors.map(CustomError::warning))
+    }
+}
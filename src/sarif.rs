@@ -0,0 +1,151 @@
+use crate::error::Severity;
+use crate::errors::CustomErrors;
+use crate::render::ErrorView;
+use serde_json::{json, Value};
+use std::fmt::Debug;
+
+impl<T: Debug> CustomErrors<T> {
+    /// Export all errors as a SARIF 2.1.0 log, only available with the `sarif` feature. Every
+    /// error becomes a result, with the code (or the kind if there is no code) as rule id. For
+    /// every distinct rule id a rule is added, with the url of the first error with that rule id
+    /// (as generated by the [CustomError!](crate::CustomError!) macro with `doc`) as help uri. Every highlight, or
+    /// every context without highlights, becomes a location. Contexts without a file are left out, as SARIF needs a file for
+    /// every location. The linenumbers of the contexts are seen as 1 based (as set by
+    /// [Context::from_span](crate::Context::from_span)).
+    /// ```
+    /// use custom_error::*;
+    /// #[derive(Debug)]
+    /// enum ErrorType {
+    ///     NotANumber,
+    /// }
+    /// let source = Source::new("numbers.txt", "help 123\nhelp l23\n");
+    /// let mut errors = CustomErrors::new();
+    /// errors += CustomError::new(ErrorType::NotANumber)
+    ///     .message("The value provided was not a valid number")
+    ///     .context(Context::from_span(&source, 14..17));
+    /// let sarif = errors.to_sarif("number-parser");
+    /// let result = &sarif["runs"][0]["results"][0];
+    /// assert_eq!(result["level"], "error");
+    /// let region = &result["locations"][0]["physicalLocation"]["region"];
+    /// assert_eq!(region["startLine"], 2);
+    /// assert_eq!(region["startColumn"], 6);
+    /// ```
+    pub fn to_sarif(&self, tool_name: &str) -> Value {
+        let views: Vec<ErrorView> = self.iter().map(|e| e.view()).collect();
+        let mut rules: Vec<&ErrorView> = Vec::new();
+        let mut results = Vec::new();
+        for view in &views {
+            let rule_index = match rules
+                .iter()
+                .position(|rule| rule.identifier() == view.identifier())
+            {
+                Some(index) => index,
+                None => {
+                    rules.push(view);
+                    rules.len() - 1
+                }
+            };
+            // Use the url of a later error of this kind if the first did not have one
+            if rules[rule_index].url.is_none() && view.url.is_some() {
+                rules[rule_index] = view;
+            }
+            results.push(result(view, rule_index));
+        }
+
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": tool_name,
+                        "rules": rules.iter().map(|view| rule(view)).collect::<Vec<_>>(),
+                    }
+                },
+                "results": results,
+            }]
+        })
+    }
+}
+
+/// The SARIF level for the given level
+fn level(level: Severity) -> &'static str {
+    match level {
+        Severity::Error | Severity::Fatal | Severity::Bug => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Note | Severity::Help | Severity::Hint => "note",
+    }
+}
+
+/// The rule descriptor for the kind of the given error
+fn rule(view: &ErrorView) -> Value {
+    let mut rule = json!({
+        "id": view.identifier(),
+        "defaultConfiguration": { "level": level(view.level) },
+    });
+    if let Some(title) = view.title {
+        rule["shortDescription"] = json!({ "text": title });
+    }
+    if let Some(url) = view.url {
+        rule["helpUri"] = json!(url);
+    }
+    rule
+}
+
+/// The result for the given error
+fn result(view: &ErrorView, rule_index: usize) -> Value {
+    let mut locations = Vec::new();
+    for context in &view.contexts {
+        let file = match context.file {
+            Some(file) => file,
+            None => continue,
+        };
+        let first_line = context.linenumber.unwrap_or(1);
+        // All lines of the context, which is also the region if there are no highlights
+        let lines = json!({
+            "startLine": first_line,
+            "endLine": first_line + context.lines.len().saturating_sub(1),
+            "snippet": { "text": context.lines.join("\n") },
+        });
+        if context.highlights.is_empty() {
+            locations.push(json!({
+                "physicalLocation": {
+                    "artifactLocation": { "uri": file },
+                    "region": lines,
+                }
+            }));
+        }
+        for highlight in &context.highlights {
+            let (end_line, end_column) = highlight.end_position();
+            let mut location = json!({
+                "physicalLocation": {
+                    "artifactLocation": { "uri": file },
+                    // SARIF lines and columns are 1 based, with columns in UTF-16 code units
+                    "region": {
+                        "startLine": first_line + highlight.line,
+                        "startColumn": context.utf16_column(highlight.line, highlight.column) + 1,
+                        "endLine": first_line + end_line,
+                        "endColumn": context.utf16_column(end_line, end_column) + 1,
+                    },
+                    "contextRegion": lines,
+                }
+            });
+            if let Some(note) = highlight.note {
+                location["message"] = json!({ "text": note });
+            }
+            locations.push(location);
+        }
+    }
+
+    let mut result = json!({
+        "ruleId": view.identifier(),
+        "ruleIndex": rule_index,
+        "level": level(view.level),
+        "message": { "text": view.plain_message() },
+        "locations": locations,
+    });
+    if let Some(location) = view.location {
+        result["properties"] = json!({ "generatedAt": location });
+    }
+    result
+}
@@ -0,0 +1,120 @@
+use std::ops::Range;
+use std::path::Path;
+
+/// The full text of a source file, together with its name. This can be used to create a
+/// [Context](crate::Context) from byte offsets, see [Context::from_span](crate::Context::from_span).
+/// ```
+/// use custom_error::*;
+/// let source = Source::new("numbers.txt", "help 123\nhelp l23\n");
+/// assert_eq!(source.name(), Some("numbers.txt"));
+/// assert_eq!(source.line_index(12), 1);
+/// assert_eq!(source.line(1), Some("help l23"));
+/// assert_eq!(source.line_count(), 2);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Source {
+    name: Option<String>,
+    text: String,
+    /// The byte offset of the start of every line
+    line_starts: Vec<usize>,
+}
+
+impl Source {
+    /// Create a new source with the given name (generally the path of the file) and text
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::anonymous(text)
+        }
+    }
+
+    /// Create a new source without a name, for example for text read from stdin
+    pub fn anonymous(text: impl Into<String>) -> Self {
+        let text = text.into();
+        // A final line ending does not start a new line, but an empty text is still a single line
+        let line_starts = std::iter::once(0)
+            .chain(
+                text.match_indices('\n')
+                    .map(|(index, _)| index + 1)
+                    .filter(|start| *start < text.len()),
+            )
+            .collect();
+        Self {
+            name: None,
+            text,
+            line_starts,
+        }
+    }
+
+    /// Read the file at the given path, the path is used as the name of the source.
+    pub fn read(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        Ok(Self::new(
+            path.to_string_lossy(),
+            std::fs::read_to_string(path)?,
+        ))
+    }
+
+    /// The name of this source, if given
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The full text of this source
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The number of lines in this source, a line ending at the end of the text does not count as
+    /// an extra line.
+    /// ```
+    /// use custom_error::*;
+    /// assert_eq!(Source::anonymous("ab\r\ncd\r\n").line_count(), 2);
+    /// assert_eq!(Source::anonymous("ab\ncd").line_count(), 2);
+    /// assert_eq!(Source::anonymous("").line_count(), 1);
+    /// ```
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Get the (0 based) index of the line containing the given byte offset. Offsets past the
+    /// end of the text result in the last line.
+    pub fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(index) => index,
+            Err(index) => index - 1,
+        }
+    }
+
+    /// Get the byte range of the line with the given (0 based) index, excluding the line ending
+    pub fn line_range(&self, index: usize) -> Option<Range<usize>> {
+        let start = *self.line_starts.get(index)?;
+        let end = self
+            .line_starts
+            .get(index + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        let line = &self.text[start..end];
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        Some(start..start + line.len())
+    }
+
+    /// Get the text of the line with the given (0 based) index, excluding the line ending
+    pub fn line(&self, index: usize) -> Option<&str> {
+        self.line_range(index).map(|range| &self.text[range])
+    }
+
+    /// Iterate over all lines in this source, together with the byte offset of the start of each line
+    pub fn lines(&self) -> impl Iterator<Item = (usize, &str)> {
+        (0..self.line_count()).map(|index| (self.line_starts[index], self.line(index).unwrap()))
+    }
+
+    /// Move the given byte offset back to the closest char boundary, and make sure it is inside the text
+    pub(crate) fn floor_char_boundary(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
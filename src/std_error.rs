@@ -0,0 +1,157 @@
+use crate::context::Context;
+use crate::error::CustomError;
+use crate::source::Source;
+use std::error::Error;
+use std::num::{IntErrorKind, ParseFloatError, ParseIntError};
+use std::ops::Range;
+use std::str::Utf8Error;
+
+/// The kind of errors converted from errors of the standard library. This wrapper is needed
+/// because `CustomError<std::io::Error>` cannot be converted into other kinds with
+/// [CustomError::convert], see the note there.
+/// ```
+/// use custom_error::*;
+/// fn read(path: &str) -> Result<String, CustomError<StdErrorKind>> {
+///     Ok(std::fs::read_to_string(path)?)
+/// }
+/// let error = read("does/not/exist.txt").unwrap_err();
+/// assert_eq!(error.kind(), &StdErrorKind::Io(std::io::ErrorKind::NotFound));
+/// assert!(std::error::Error::source(&error).is_some());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[non_exhaustive]
+pub enum StdErrorKind {
+    /// An [std::io::Error] of the given kind
+    Io(std::io::ErrorKind),
+    /// A [ParseIntError] of the given kind
+    ParseInt(IntErrorKind),
+    /// A [ParseFloatError]
+    ParseFloat,
+    /// A [Utf8Error]
+    Utf8,
+}
+
+impl From<std::io::Error> for CustomError<StdErrorKind> {
+    fn from(error: std::io::Error) -> Self {
+        CustomError::new(StdErrorKind::Io(error.kind()))
+            .title("Input/output error")
+            .source(error)
+    }
+}
+
+impl From<ParseIntError> for CustomError<StdErrorKind> {
+    fn from(error: ParseIntError) -> Self {
+        from_input_error(error)
+    }
+}
+
+impl From<ParseFloatError> for CustomError<StdErrorKind> {
+    fn from(error: ParseFloatError) -> Self {
+        from_input_error(error)
+    }
+}
+
+impl From<Utf8Error> for CustomError<StdErrorKind> {
+    fn from(error: Utf8Error) -> Self {
+        from_input_error(error)
+    }
+}
+
+/// Create an error for the given input error, without the input
+fn from_input_error(error: impl InputError) -> CustomError<StdErrorKind> {
+    CustomError::new(error.kind())
+        .title(error.title())
+        .source(error)
+}
+
+/// An error of the standard library caused by invalid input, which can be shown with a context
+/// around the offending input, see [CustomErrorInputUnwrap].
+pub trait InputError: Error + Send + Sync + Sized + 'static {
+    /// The kind of this error
+    fn kind(&self) -> StdErrorKind;
+
+    /// The title of this error
+    fn title(&self) -> &'static str;
+
+    /// The span of byte offsets in the input to highlight
+    fn span(&self, input: &[u8]) -> Range<usize> {
+        0..input.len()
+    }
+}
+
+impl InputError for ParseIntError {
+    fn kind(&self) -> StdErrorKind {
+        StdErrorKind::ParseInt(*self.kind())
+    }
+
+    fn title(&self) -> &'static str {
+        "Invalid integer"
+    }
+}
+
+impl InputError for ParseFloatError {
+    fn kind(&self) -> StdErrorKind {
+        StdErrorKind::ParseFloat
+    }
+
+    fn title(&self) -> &'static str {
+        "Invalid number"
+    }
+}
+
+impl InputError for Utf8Error {
+    fn kind(&self) -> StdErrorKind {
+        StdErrorKind::Utf8
+    }
+
+    fn title(&self) -> &'static str {
+        "Invalid UTF-8"
+    }
+
+    /// The invalid bytes after the valid start of the input
+    fn span(&self, input: &[u8]) -> Range<usize> {
+        let start = self.valid_up_to();
+        start
+            ..self
+                .error_len()
+                .map_or(input.len(), |length| start + length)
+    }
+}
+
+/// A trait to help with creating Custom Errors from standard library errors caused by invalid
+/// input, with a context highlighting the offending input.
+#[allow(clippy::result_large_err)] // Returns the error unboxed, like CustomErrorUnwrap
+pub trait CustomErrorInputUnwrap<T> {
+    /// Use this to create a new error with a context around the given input, which is the input
+    /// that caused the error. Invalid UTF-8 is shown as `�` in the context.
+    /// ```
+    /// use custom_error::*;
+    /// let error = "12e".parse::<usize>().unwrap_or_input_error("12e").unwrap_err();
+    /// assert_eq!(error.kind(), &StdErrorKind::ParseInt(std::num::IntErrorKind::InvalidDigit));
+    ///
+    /// let input = b"valid \xF0\x90\x80 invalid";
+    /// let error = std::str::from_utf8(input).unwrap_or_input_error(input).unwrap_err();
+    /// let output = error.with_colour(ColourChoice::Never).to_string();
+    /// assert!(output.contains("valid � invalid"));
+    /// assert!(output.contains("       ─\n"));
+    /// ```
+    fn unwrap_or_input_error(self, input: impl AsRef<[u8]>)
+        -> Result<T, CustomError<StdErrorKind>>;
+}
+
+impl<T, E: InputError> CustomErrorInputUnwrap<T> for Result<T, E> {
+    fn unwrap_or_input_error(
+        self,
+        input: impl AsRef<[u8]>,
+    ) -> Result<T, CustomError<StdErrorKind>> {
+        self.map_err(|error| {
+            let input = input.as_ref();
+            let span = error.span(input);
+            // Every sequence of invalid bytes is replaced by a single replacement character
+            let source = Source::anonymous(String::from_utf8_lossy(input));
+            let start = String::from_utf8_lossy(&input[..span.start]).len();
+            let end = start + String::from_utf8_lossy(&input[span]).len();
+            from_input_error(error).context(Context::from_span(&source, start..end))
+        })
+    }
+}
@@ -0,0 +1,290 @@
+use crate::errors::CustomErrors;
+use crate::source::Source;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// How sure a suggestion is, which determines if it can be applied automatically.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Applicability {
+    /// The suggestion is definitely correct and can be applied automatically, see [apply_suggestions]
+    MachineApplicable,
+    /// The suggestion is probably correct, but should be reviewed before it is applied
+    MaybeIncorrect,
+    /// The suggestion contains placeholders, like `<type>`, which have to be filled in
+    HasPlaceholders,
+    /// It is not known how sure the suggestion is
+    #[default]
+    Unspecified,
+}
+
+/// A suggestion to replace a span of a [Source] with another text, displayed as a preview of the
+/// changed lines. Add it to an error with [CustomError::suggestion](crate::CustomError::suggestion).
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// let source = Source::new("numbers.txt", "help 123\nhelp l23\n");
+/// let error = CustomError::new(ErrorType::NotANumber)
+///     .context(Context::from_span(&source, 14..17))
+///     .suggestion(
+///         Suggestion::new(&source, 14..15, "1", Applicability::MaybeIncorrect)
+///             .message("did you mean a one"),
+///     );
+/// let output = error.with_colour(ColourChoice::Never).to_string();
+/// assert!(output.contains("suggestion: did you mean a one"));
+/// assert!(output.contains("2 - help l23"));
+/// assert!(output.contains("2 + help 123"));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Suggestion {
+    file: Option<String>,
+    span: Range<usize>,
+    replacement: String,
+    applicability: Applicability,
+    message: Option<String>,
+    /// The (1 based) linenumber of the first changed line
+    linenumber: usize,
+    /// The lines containing the span, before the replacement
+    removed: Vec<String>,
+    /// The same lines after the replacement
+    added: Vec<String>,
+}
+
+impl Suggestion {
+    /// Create a suggestion to replace the given span of byte offsets in the source with the given
+    /// text. The span is moved to the closest char boundaries before the given offsets.
+    pub fn new(
+        source: &Source,
+        span: Range<usize>,
+        replacement: impl Into<String>,
+        applicability: Applicability,
+    ) -> Self {
+        let start = source.floor_char_boundary(span.start);
+        let end = source.floor_char_boundary(span.end).max(start);
+        let replacement = replacement.into();
+        let text = source.text();
+        // If the span ends just after a line ending the changed lines end there as well
+        let ends_line = end > start && text.as_bytes()[end - 1] == b'\n';
+        let first = source.line_index(start);
+        let last = source.line_index(if ends_line { end - 1 } else { end });
+        let lines_start = source.line_range(first).unwrap().start;
+        let lines_end = if ends_line {
+            end
+        } else {
+            source.line_range(last).unwrap().end.max(end)
+        };
+        let added = format!(
+            "{}{}{}",
+            &text[lines_start..start],
+            replacement,
+            &text[end..lines_end]
+        );
+        Suggestion {
+            file: source.name().map(str::to_string),
+            span: start..end,
+            replacement,
+            applicability,
+            message: None,
+            linenumber: first + 1,
+            removed: split_lines(&text[lines_start..lines_end], ends_line),
+            added: split_lines(&added, ends_line),
+        }
+    }
+
+    /// Add a message to display above the preview of the suggestion
+    pub fn message(self, message: impl Into<String>) -> Self {
+        Suggestion {
+            message: Some(message.into()),
+            ..self
+        }
+    }
+
+    /// Check if this suggestion still fits the given source: the span is inside the text, on char
+    /// boundaries, and the changed lines are the same as when this suggestion was made.
+    fn fits(&self, source: &Source) -> bool {
+        let text = source.text();
+        self.span.start <= self.span.end
+            && self.span.end <= text.len()
+            && text.is_char_boundary(self.span.start)
+            && text.is_char_boundary(self.span.end)
+            && {
+                let current = Suggestion::new(
+                    source,
+                    self.span.clone(),
+                    self.replacement.as_str(),
+                    self.applicability,
+                );
+                current.linenumber == self.linenumber && current.removed == self.removed
+            }
+    }
+
+    /// Get a structured view of this suggestion, as used by a [Renderer](crate::Renderer).
+    pub fn view(&self) -> SuggestionView<'_> {
+        SuggestionView {
+            file: self.file.as_deref(),
+            span: self.span.clone(),
+            replacement: &self.replacement,
+            applicability: self.applicability,
+            message: self.message.as_deref(),
+            linenumber: self.linenumber,
+            removed: &self.removed,
+            added: &self.added,
+        }
+    }
+}
+
+/// Split the given text into lines, an empty text is still a single (empty) line. If the text is
+/// `terminated` every line ends with a line ending, so an empty text has no lines.
+fn split_lines(text: &str, terminated: bool) -> Vec<String> {
+    let text = match (terminated, text.strip_suffix('\n')) {
+        (true, _) if text.is_empty() => return Vec::new(),
+        (true, Some(text)) => text,
+        _ => text,
+    };
+    text.split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+        .collect()
+}
+
+/// A structured view of a [Suggestion], as given to a [Renderer](crate::Renderer).
+#[non_exhaustive]
+#[derive(Debug, Clone)]
+pub struct SuggestionView<'a> {
+    /// The name of the source the suggestion is for, if it has one
+    pub file: Option<&'a str>,
+    /// The span of byte offsets in the source to replace
+    pub span: Range<usize>,
+    /// The text to replace the span with
+    pub replacement: &'a str,
+    /// How sure the suggestion is
+    pub applicability: Applicability,
+    /// The message, if given
+    pub message: Option<&'a str>,
+    /// The (1 based) linenumber of the first changed line
+    pub linenumber: usize,
+    /// The changed lines, before the replacement
+    pub removed: &'a [String],
+    /// The changed lines, after the replacement
+    pub added: &'a [String],
+}
+
+/// Apply all [MachineApplicable](Applicability::MachineApplicable) suggestions of the given
+/// errors to the source, for example for a `--fix` mode. Only suggestions with the same file as
+/// the source are applied. Suggestions that are exactly the same are applied once, if other
+/// suggestions overlap nothing is applied and an error is returned. Nothing is applied either if
+/// a suggestion does not fit the source, for example because it was made for an earlier version.
+/// ```
+/// use custom_error::*;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+/// }
+/// let source = Source::new("numbers.txt", "help l23\nhelp 1O0\n");
+/// let mut errors = CustomErrors::new();
+/// errors += CustomError::new(ErrorType::NotANumber)
+///     .suggestion(Suggestion::new(&source, 5..6, "1", Applicability::MachineApplicable));
+/// errors += CustomError::new(ErrorType::NotANumber)
+///     .suggestion(Suggestion::new(&source, 15..16, "0", Applicability::MachineApplicable));
+/// assert_eq!(apply_suggestions(&source, &errors).unwrap(), "help 123\nhelp 100\n");
+///
+/// // The file was changed since the suggestions were made
+/// let changed = Source::new("numbers.txt", "help l23\n");
+/// assert_eq!(
+///     apply_suggestions(&changed, &errors),
+///     Err(SuggestionError::Outdated { span: 15..16 })
+/// );
+/// let ascii = Source::new("u.txt", "abc");
+/// let mut other = CustomErrors::new();
+/// other += CustomError::new(ErrorType::NotANumber)
+///     .suggestion(Suggestion::new(&ascii, 2..3, "d", Applicability::MachineApplicable));
+/// // Byte 2 is in the middle of `é`
+/// assert!(apply_suggestions(&Source::new("u.txt", "aé"), &other).is_err());
+///
+/// errors += CustomError::new(ErrorType::NotANumber)
+///     .suggestion(Suggestion::new(&source, 14..17, "100", Applicability::MachineApplicable));
+/// assert!(apply_suggestions(&source, &errors).is_err());
+/// ```
+pub fn apply_suggestions<T>(
+    source: &Source,
+    errors: &CustomErrors<T>,
+) -> Result<String, SuggestionError> {
+    let text = source.text();
+    let mut suggestions: Vec<&Suggestion> = errors
+        .iter()
+        .flat_map(|error| error.suggestions())
+        .filter(|s| {
+            s.applicability == Applicability::MachineApplicable
+                && s.file.as_deref() == source.name()
+        })
+        .collect();
+    if let Some(outdated) = suggestions.iter().find(|s| !s.fits(source)) {
+        return Err(SuggestionError::Outdated {
+            span: outdated.span.clone(),
+        });
+    }
+    suggestions.sort_by_key(|s| (s.span.start, s.span.end));
+    suggestions.dedup_by(|a, b| a.span == b.span && a.replacement == b.replacement);
+
+    let mut output = String::with_capacity(text.len());
+    let mut offset = 0;
+    for (index, suggestion) in suggestions.iter().enumerate() {
+        // Insertions at the same place also overlap, as their order is unclear
+        if index > 0
+            && (suggestion.span.start < offset
+                || suggestion.span.start == suggestions[index - 1].span.start)
+        {
+            return Err(SuggestionError::Overlapping {
+                first: suggestions[index - 1].span.clone(),
+                second: suggestion.span.clone(),
+            });
+        }
+        output += &text[offset..suggestion.span.start];
+        output += &suggestion.replacement;
+        offset = suggestion.span.end;
+    }
+    output += &text[offset..];
+    Ok(output)
+}
+
+/// The error returned by [apply_suggestions] if the suggestions cannot be applied.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum SuggestionError {
+    /// Two suggestions change the same text
+    Overlapping {
+        /// The span of the first of the overlapping suggestions
+        first: Range<usize>,
+        /// The span of the second of the overlapping suggestions
+        second: Range<usize>,
+    },
+    /// A suggestion does not fit the source, its span is outside the text or the text under it
+    /// is different from when the suggestion was made
+    Outdated {
+        /// The span of the suggestion
+        span: Range<usize>,
+    },
+}
+
+impl Display for SuggestionError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            SuggestionError::Overlapping { first, second } => write!(
+                f,
+                "the suggestions for bytes {:?} and {:?} overlap",
+                first, second
+            ),
+            SuggestionError::Outdated { span } => write!(
+                f,
+                "the suggestion for bytes {:?} does not fit the source",
+                span
+            ),
+        }
+    }
+}
+
+impl Error for SuggestionError {}
@@ -0,0 +1,272 @@
+//! Helpers to test the errors of a program, by comparing the rendered errors to stored snapshots
+//! and by checking the errors for a source file against annotations in that file.
+//!
+//! Snapshots are compared without colours. If a snapshot is missing or outdated the test fails,
+//! run the tests with the `CUSTOM_ERROR_BLESS` environment variable set to write the current
+//! output to the snapshot files instead.
+use crate::colour::*;
+use crate::error::{CustomError, Severity};
+use crate::errors::CustomErrors;
+use crate::source::Source;
+use std::fmt::{Debug, Display};
+use std::path::Path;
+
+/// The environment variable to set to update the snapshots instead of comparing them
+pub const BLESS_VAR: &str = "CUSTOM_ERROR_BLESS";
+
+/// Remove all ANSI escape sequences (like colours) from the given text.
+/// ```
+/// use custom_error::testing::strip_colours;
+/// assert_eq!(strip_colours("\x1b[1;31merror\x1b[0m: oops"), "error: oops");
+/// ```
+pub fn strip_colours(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.clone().next() == Some('[') {
+            // A control sequence ends with a byte in the range `@` to `~`
+            chars.by_ref().skip(1).find(|c| ('@'..='~').contains(c));
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+/// Render the given item (like a [CustomError] or [CustomErrors]) without colours.
+pub fn render_plain(item: &(impl Display + ?Sized)) -> String {
+    strip_colours(&scoped(Some(ColourChoice::Never), || item.to_string()))
+}
+
+/// Check that the given item (like a [CustomError] or [CustomErrors]) renders to the snapshot
+/// stored at the given path, colours are left out. If the `CUSTOM_ERROR_BLESS` environment
+/// variable is set the snapshot is written instead.
+///
+/// # Panics
+/// If the output is different from the snapshot, or if the snapshot does not exist.
+pub fn assert_snapshot(item: &(impl Display + ?Sized), path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let actual = render_plain(item);
+    if std::env::var_os(BLESS_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .unwrap_or_else(|e| panic!("could not create {}: {}", parent.display(), e));
+        }
+        std::fs::write(path, &actual)
+            .unwrap_or_else(|e| panic!("could not write {}: {}", path.display(), e));
+        return;
+    }
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) => expected.replace("\r\n", "\n"),
+        Err(e) => panic!(
+            "could not read the snapshot {}: {}\nrun with {}=1 to create it, the output is:\n{}",
+            path.display(),
+            e,
+            BLESS_VAR,
+            actual
+        ),
+    };
+    if expected != actual {
+        panic!(
+            "the output does not match the snapshot {}\n{}\nrun with {}=1 to update it",
+            path.display(),
+            diff(&expected, &actual),
+            BLESS_VAR
+        );
+    }
+}
+
+/// Show the lines that differ between the expected and actual output, prefixed with `-` for
+/// expected lines and `+` for actual lines
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+    let mut output = String::new();
+    for index in 0..expected.len().max(actual.len()) {
+        match (expected.get(index), actual.get(index)) {
+            (Some(e), Some(a)) if e == a => output += &format!(" {}\n", e),
+            (e, a) => {
+                if let Some(e) = e {
+                    output += &format!("-{}\n", e);
+                }
+                if let Some(a) = a {
+                    output += &format!("+{}\n", a);
+                }
+            }
+        }
+    }
+    output
+}
+
+/// An expected error, written in a comment in a source file
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Annotation {
+    /// The (1 based) line of the error, or none for errors without a location
+    line: Option<usize>,
+    level: Severity,
+    /// The code or the name of the kind of the error, if given
+    kind: Option<String>,
+}
+
+impl Annotation {
+    /// Check if the given error is expected by this annotation
+    fn matches<T: Debug>(&self, error: &CustomError<T>, line: Option<usize>) -> bool {
+        self.line == line
+            && self.level == error.level()
+            && self.kind.as_ref().is_none_or(|kind| {
+                error.code() == Some(kind.as_str()) || *kind == format!("{:?}", error.kind())
+            })
+    }
+}
+
+impl Display for Annotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: ", line)?,
+            None => write!(f, "without location: ")?,
+        }
+        write!(f, "{}", self.level.name())?;
+        if let Some(kind) = &self.kind {
+            write!(f, " {}", kind)?;
+        }
+        Ok(())
+    }
+}
+
+/// Read all annotations in the given source, see [check_annotations].
+fn annotations(source: &Source) -> Result<Vec<Annotation>, String> {
+    let mut annotations: Vec<Annotation> = Vec::new();
+    for (index, (_, line)) in source.lines().enumerate() {
+        let Some((_, annotation)) = line.split_once("//~") else {
+            continue;
+        };
+        let (target, rest) = match annotation.chars().next() {
+            Some('?') => (None, &annotation[1..]),
+            Some('|') => (
+                annotations
+                    .last()
+                    .map(|previous| previous.line)
+                    .ok_or_else(|| {
+                        format!("line {}: `//~|` without a previous annotation", index + 1)
+                    })?,
+                &annotation[1..],
+            ),
+            _ => {
+                let up = annotation.len() - annotation.trim_start_matches('^').len();
+                let line = (index + 1).checked_sub(up).filter(|line| *line > 0);
+                (
+                    Some(line.ok_or_else(|| {
+                        format!("line {}: annotation points before the start", index + 1)
+                    })?),
+                    &annotation[up..],
+                )
+            }
+        };
+        let mut words = rest.split_whitespace();
+        let level = words.next().unwrap_or_default();
+        let level = Severity::all()
+            .into_iter()
+            .find(|severity| severity.name().to_uppercase() == level)
+            .ok_or_else(|| format!("line {}: unknown level `{}`", index + 1, level))?;
+        annotations.push(Annotation {
+            line: target,
+            level,
+            kind: words.next().map(str::to_string),
+        });
+    }
+    Ok(annotations)
+}
+
+/// Check the given errors against the annotations in the given source, every error should be
+/// annotated and every annotation should match an error. An annotation is a comment like
+/// `//~ ERROR kind`, with the level in capitals followed by the code or the name of the kind of
+/// the error (as displayed by [Debug]). The kind can be left out to match any error of that level.
+/// The annotation refers to the line it is on, `//~^` refers to the line above (with one `^` for
+/// every line up), `//~|` refers to the same line as the previous annotation and `//~?` refers to
+/// errors without a location. Errors are located by their first context. If the check fails a
+/// description of all unexpected and missing errors is returned.
+/// ```
+/// use custom_error::*;
+/// use custom_error::testing::check_annotations;
+/// #[derive(Debug)]
+/// enum ErrorType {
+///     NotANumber,
+///     UnusedValue,
+/// }
+/// let source = Source::new("numbers.txt", "12\nl2 //~ ERROR NotANumber\n3\n//~^ WARNING\n");
+/// let mut errors = CustomErrors::new();
+/// errors += CustomError::new(ErrorType::NotANumber).context(Context::from_span(&source, 3..5));
+/// errors += CustomError::new(ErrorType::UnusedValue).warning().context(Context::from_span(&source, 27..28));
+/// assert_eq!(check_annotations(&source, &errors), Ok(()));
+///
+/// errors += CustomError::new(ErrorType::UnusedValue);
+/// assert_eq!(
+///     check_annotations(&source, &errors),
+///     Err("unexpected error without location: error UnusedValue\n".to_string())
+/// );
+/// ```
+pub fn check_annotations<T: Debug>(
+    source: &Source,
+    errors: &CustomErrors<T>,
+) -> Result<(), String> {
+    let mut expected = annotations(source)?;
+    let mut report = String::new();
+    for error in errors.iter() {
+        let line = error
+            .position()
+            .map(|(_, line, _)| line)
+            .filter(|line| *line > 0);
+        match expected.iter().position(|a| a.matches(error, line)) {
+            Some(index) => {
+                expected.remove(index);
+            }
+            None => {
+                let unexpected = Annotation {
+                    line,
+                    level: error.level(),
+                    kind: Some(
+                        error
+                            .code()
+                            .map_or_else(|| format!("{:?}", error.kind()), str::to_string),
+                    ),
+                };
+                report += &format!("unexpected error {}\n", unexpected);
+            }
+        }
+    }
+    for missing in expected {
+        report += &format!("missing error {}\n", missing);
+    }
+    if report.is_empty() {
+        Ok(())
+    } else {
+        Err(report)
+    }
+}
+
+/// Run a UI test for the source file at the given path: the errors produced by the given function
+/// for this source are checked against the annotations in the file (see [check_annotations]) and
+/// the rendered errors are compared to the snapshot next to the file, with the extension `stderr`
+/// (see [assert_snapshot]).
+///
+/// # Panics
+/// If the file cannot be read, if the errors do not match the annotations, or if the output does
+/// not match the snapshot.
+pub fn run_ui_test<T: Debug>(
+    path: impl AsRef<Path>,
+    check: impl FnOnce(&Source) -> CustomErrors<T>,
+) {
+    let path = path.as_ref();
+    let source =
+        Source::read(path).unwrap_or_else(|e| panic!("could not read {}: {}", path.display(), e));
+    let errors = check(&source);
+    if let Err(report) = check_annotations(&source, &errors) {
+        panic!(
+            "the errors for {} do not match the annotations\n{}\nthe output is:\n{}",
+            path.display(),
+            report,
+            render_plain(&errors)
+        );
+    }
+    assert_snapshot(&errors, path.with_extension("stderr"));
+}
@@ -0,0 +1,104 @@
+//! Snapshots of the rendered output, run with `CUSTOM_ERROR_BLESS=1` to update them.
+use custom_error::testing::assert_snapshot;
+use custom_error::*;
+
+#[derive(Debug)]
+enum ErrorType {
+    NotANumber,
+    UnusedValue,
+    DuplicateDefinition,
+}
+
+fn snapshot(name: &str) -> String {
+    format!("tests/snapshots/{}.stderr", name)
+}
+
+#[test]
+fn single_line() {
+    let source = Source::new("numbers.txt", "12\nl2\n3\n");
+    let error = CustomError::new(ErrorType::NotANumber)
+        .title("Invalid number")
+        .context(Context::from_span(&source, 3..4).note("not a digit"));
+    assert_snapshot(&error, snapshot("single_line"));
+}
+
+#[test]
+fn multiline_highlight() {
+    let source = Source::new("list.txt", "values = [\n    1,\n    2\n]\n");
+    let error = CustomError::new(ErrorType::UnusedValue)
+        .warning()
+        .context(Context::from_span(&source, 9..24))
+        .help("Remove the list");
+    assert_snapshot(&error, snapshot("multiline_highlight"));
+}
+
+#[test]
+fn multiple_highlights() {
+    let error = CustomError::new(ErrorType::DuplicateDefinition)
+        .message("The name `a` is defined multiple times")
+        .context(
+            Context::line("let a = 1; let a = 2;")
+                .linenumber(7)
+                .highlight(Highlight::new(0, 4, 1).note("first definition").info())
+                .highlight(Highlight::new(0, 15, 1).note("redefined here")),
+        );
+    assert_snapshot(&error, snapshot("multiple_highlights"));
+}
+
+#[test]
+fn children_and_causes() {
+    let source = Source::new("defs.txt", "let a = 1;\nlet a = 2;\n");
+    let cause = "x".parse::<usize>().unwrap_err();
+    let error = CustomError::new(ErrorType::DuplicateDefinition)
+        .context(Context::from_span(&source, 15..16))
+        .child(
+            Severity::Note,
+            "previous definition here",
+            Some(Context::from_span(&source, 4..5)),
+        )
+        .child(Severity::Help, "rename one of the definitions", None)
+        .source(cause);
+    assert_snapshot(&error, snapshot("children_and_causes"));
+}
+
+#[test]
+fn suggestion() {
+    let source = Source::new("numbers.txt", "12\nl2\n3\n");
+    let error = CustomError::new(ErrorType::NotANumber).suggestion(
+        Suggestion::new(&source, 3..4, "1", Applicability::MaybeIncorrect)
+            .message("Did you mean a one?"),
+    );
+    assert_snapshot(&error, snapshot("suggestion"));
+}
+
+#[test]
+fn suggestion_empty_line() {
+    let source = Source::new("y.txt", "a\n\nb\n");
+    let error = CustomError::new(ErrorType::UnusedValue).suggestion(
+        Suggestion::new(&source, 2..2, "x", Applicability::MachineApplicable)
+            .message("Insert a value"),
+    );
+    assert_snapshot(&error, snapshot("suggestion_empty_line"));
+}
+
+#[test]
+fn suggestion_remove_last_line() {
+    let source = Source::new("z.txt", "abc\ndef\n");
+    let error = CustomError::new(ErrorType::UnusedValue).suggestion(
+        Suggestion::new(&source, 4..8, "", Applicability::MachineApplicable)
+            .message("Remove the line"),
+    );
+    assert_snapshot(&error, snapshot("suggestion_remove_last_line"));
+}
+
+#[test]
+fn summary() {
+    let source = Source::new("numbers.txt", "l2\n3\n4O\n");
+    let mut errors = CustomErrors::new();
+    errors += CustomError::new(ErrorType::NotANumber).context(Context::from_span(&source, 0..1));
+    errors += CustomError::new(ErrorType::UnusedValue)
+        .warning()
+        .context(Context::from_span(&source, 3..4));
+    errors += CustomError::new(ErrorType::NotANumber).context(Context::from_span(&source, 6..8));
+    assert_snapshot(&errors, snapshot("summary"));
+}
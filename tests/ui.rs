@@ -0,0 +1,49 @@
+//! UI tests checking the annotations in the files in `tests/ui`, run with `CUSTOM_ERROR_BLESS=1`
+//! to update the snapshots.
+use custom_error::testing::run_ui_test;
+use custom_error::*;
+
+#[derive(Debug)]
+enum ErrorType {
+    NotANumber,
+    Empty,
+}
+
+/// Check that every line is a number, the annotations are left out
+fn check_numbers(source: &Source) -> CustomErrors<ErrorType> {
+    let mut errors = CustomErrors::new();
+    for (start, line) in source.lines() {
+        if line.starts_with("//~") {
+            continue;
+        }
+        let value = line.split("//~").next().unwrap().trim_end();
+        if value.is_empty() {
+            errors += CustomError::new(ErrorType::Empty)
+                .warning()
+                .context(Context::from_span(source, start..start));
+        } else if value.parse::<isize>().is_err() {
+            errors += CustomError::new(ErrorType::NotANumber)
+                .context(Context::from_span(source, start..start + value.len()));
+        }
+    }
+    if source.text().is_empty() {
+        errors += CustomError::new(ErrorType::Empty).message("The file is empty");
+    }
+    errors
+}
+
+#[test]
+fn numbers() {
+    run_ui_test("tests/ui/numbers.txt", check_numbers);
+}
+
+#[test]
+fn valid() {
+    run_ui_test("tests/ui/valid.txt", check_numbers);
+}
+
+#[test]
+#[should_panic(expected = "missing error line 2: error NotANumber")]
+fn missing_errors() {
+    run_ui_test("tests/ui/numbers.txt", |_| CustomErrors::<ErrorType>::new());
+}